@@ -16,7 +16,7 @@ use serde::{Deserialize, Serialize, };
 ///
 /// This enumeration includes intervals from one second (`S1`) up to one week (`W1`).
 /// The values are represented in seconds. For instance, `M1` represents one minute, which is 60 seconds.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Intervals {
     S1  = 1,
     M1  = 60,
@@ -270,13 +270,13 @@ pub fn coinbase(kline_params: KlineParams) -> Result<Vec<KlinesSubset>, Box<dyn
             Some(io_error) if io_error.kind() == io::ErrorKind::NotFound => {
                 println!("{}", io_error);
                 println!("File not found. Pulling data from remote");
-                candles::remote_to_file(&base_url, granularity, limit, &symbol)
+                candles::remote_to_file(base_url, granularity, limit, &symbol)
             },
             _ => Err(error),
         })?;
 
     let mut klines_data = klines_res;
-    klines_data.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    klines_data.sort_by_key(|a| a.timestamp);
     klines_data.pop(); // removing last tik index, since tik hasn't yet completed
 
     let time_stamp_offset = granularity as u64 * 1000;
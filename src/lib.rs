@@ -1,8 +1,8 @@
 //! # Financial Technical Analysis Library
 //!
 //! `oscillatorsetups` is a financial technical analysis library, focused on determining the most
-//! profitable configurations for various technical oscillators, including but not limited to the Stochastic Oscillator, MACD,
-//! RSI, and others in development.
+//! profitable configurations for various technical oscillators, including but not limited to the Stochastic Oscillator, RSI,
+//! MACD, and others in development.
 //!
 //! ## Modules:
 //! * `oscillators`: Dedicated to various financial technical analysis oscillators.
@@ -37,7 +37,8 @@
 //!     Ok(s) => s
 //!         //.exchange_fee(0.00075)  // Default None
 //!         .min_qty(0.0001)        // Default None
-//!         .min_price(0.01),       // Default None
+//!         .min_price(0.01)        // Default None
+//!         .pnl_fast(true),        // Default false, screens with f64 then re-scores the top results precisely
 //!     Err(e) => {
 //!         eprintln!("Failed to create Stochastic: {}", e);
 //!         return;
@@ -49,7 +50,7 @@
 //!     k_smoothing: 3..=42,
 //!     d_length: 3..=42,
 //! });
-//! for (profit, params) in &*top_profits.lock().unwrap() {
+//! for (profit, params) in &top_profits {
 //!     println!("Net profit: {}, Parameters: {:?}", profit.0, params);
 //! }
 //!
@@ -0,0 +1,61 @@
+use crate::oscillators::models::Hlc;
+
+/// Calculates the Average True Range (ATR) for a slice of price data.
+///
+/// The true range of a tick is the greatest of its high-low range and the distances of its high and low
+/// to the previous close, the first tick uses its high-low range only. The true ranges are smoothed with
+/// Wilder's moving average: the first average is a simple mean over the first `length` true ranges,
+/// every following average is `(previous * (length - 1) + current) / length`.
+///
+/// # Arguments
+/// * `price_data` - A slice of `Hlc` structs representing the price data.
+/// * `length` - The number of periods used for the Wilder smoothing of the true range.
+///
+/// # Returns
+/// A vector of `Option<f64>`, each containing the ATR for the corresponding tick,
+/// or `None` for the first `length - 1` ticks where there is insufficient data.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::oscillators::{models::Hlc, atr::atr_for_ticks};
+///
+/// let price_data = vec![
+///     Hlc::new(2.0, 1.0, 1.5),
+///     Hlc::new(4.0, 2.0, 3.0),
+///     Hlc::new(3.5, 3.0, 3.0),
+///     Hlc::new(6.0, 5.0, 5.5),
+/// ];
+///
+/// assert_eq!(atr_for_ticks(&price_data, 2), vec![None, Some(1.75), Some(1.125), Some(2.0625)]);
+/// ```
+pub fn atr_for_ticks(price_data: &[Hlc], length: u16) -> Vec<Option<f64>> {
+    let length = length as usize;
+    let mut res = vec![None; price_data.len()];
+
+    if length == 0 || price_data.len() < length {
+        return res;
+    }
+
+    let mut atr = 0.0;
+
+    for (ix, hlc) in price_data.iter().enumerate() {
+        let range = hlc.price_high - hlc.price_low;
+        let true_range = match ix.checked_sub(1).map(|prev| price_data[prev].price_close) {
+            Some(prev_close) => range
+                .max((hlc.price_high - prev_close).abs())
+                .max((hlc.price_low - prev_close).abs()),
+            None => range,
+        };
+
+        if ix < length {
+            // Seed the average with the simple mean of the first `length` true ranges
+            atr += true_range / length as f64;
+            if ix + 1 < length { continue; }
+        } else {
+            atr = (atr * (length - 1) as f64 + true_range) / length as f64;
+        }
+
+        res[ix] = Some(atr);
+    }
+    res
+}
@@ -0,0 +1,53 @@
+/// Calculates the Exponential Moving Average (EMA) for a given vector of `Option<f64>` data over a specified period size.
+///
+/// The smoothing factor is `2 / (period + 1)`. The first EMA value is seeded with the simple average of the first `period`
+/// available values, every following value is `alpha * current + (1 - alpha) * previous`.
+/// A `None` in `data` resets the average, which is then seeded again once `period` consecutive values are available.
+///
+/// # Arguments
+/// * `data` - A slice of `Option<f64>` values for which the EMA should be calculated. Each `Option<f64>` represents a possible price at a given tick.
+/// * `period` - The number of elements the EMA is seeded with and which determines its smoothing factor.
+///
+/// # Returns
+/// * `Vec<Option<f64>>` - A vector where each element is an Option that holds the EMA at that tick,
+///   or None if there's not enough preceding data to compute it.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::oscillators::ema::ema_for_ticks;
+///
+/// let data = vec![Some(1.0), Some(2.0), Some(3.0), Some(4.0), Some(5.0)];
+///
+/// assert_eq!(ema_for_ticks(&data, 3), vec![None, None, Some(2.0), Some(3.0), Some(4.0)]);
+/// ```
+pub fn ema_for_ticks(data: &[Option<f64>], period: u16) -> Vec<Option<f64>> {
+    let period = period as usize;
+    let mut res = vec![None; data.len()];
+    if period == 0 { return res; }
+
+    let alpha = 2.0 / (period as f64 + 1.0);
+
+    let mut ema: Option<f64> = None;
+    let mut seed_sum = 0.0;
+    let mut seed_count: usize = 0;
+
+    for (ix, value) in data.iter().enumerate() {
+        let Some(value) = *value else {
+            ema = None;
+            seed_sum = 0.0;
+            seed_count = 0;
+            continue;
+        };
+
+        ema = match ema {
+            Some(previous) => Some(alpha * value + (1.0 - alpha) * previous),
+            None => {
+                seed_sum += value;
+                seed_count += 1;
+                if seed_count == period { Some(seed_sum / period as f64) } else { None }
+            }
+        };
+        res[ix] = ema;
+    }
+    res
+}
@@ -0,0 +1,74 @@
+use crate::oscillators::{models::Hlc, ema::ema_for_ticks};
+
+/// Represents the Moving Average Convergence Divergence (MACD) values at a single tick.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::oscillators::macd::MacdValues;
+///
+/// let macd = MacdValues {
+///     macd_line: Some(1.5),
+///     signal_line: Some(1.0),
+///     histogram: Some(0.5),
+/// };
+/// ```
+#[derive(PartialEq, Debug)]
+pub struct MacdValues {
+    pub macd_line: Option<f64>,
+    pub signal_line: Option<f64>,
+    pub histogram: Option<f64>,
+}
+
+/// Generates the MACD values for a slice of price data.
+///
+/// The MACD line is the difference between the fast and the slow EMA of `price_close`, the signal line is an EMA
+/// of the MACD line, and the histogram is the difference between the MACD line and the signal line.
+///
+/// # Arguments
+/// * `price_data` - A slice of `Hlc` representing the price data.
+/// * `fast_length` - The period length of the fast EMA.
+/// * `slow_length` - The period length of the slow EMA.
+/// * `signal_length` - The period length of the signal line EMA.
+///
+/// # Returns
+/// A vector of [MacdValues], each representing the MACD values at a corresponding tick.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::oscillators::{models::Hlc, macd::{macd, MacdValues}};
+///
+/// let price_data: Vec<Hlc> = (0..5).map(|_| Hlc::new(10.0, 10.0, 10.0)).collect();
+///
+/// let macd_values = macd(&price_data, 2, 3, 2);
+/// assert_eq!(macd_values[1], MacdValues { macd_line: None, signal_line: None, histogram: None });
+/// assert_eq!(macd_values[2], MacdValues { macd_line: Some(0.0), signal_line: None, histogram: None });
+/// assert_eq!(macd_values[3], MacdValues { macd_line: Some(0.0), signal_line: Some(0.0), histogram: Some(0.0) });
+/// ```
+pub fn macd(
+    price_data: &[Hlc],
+    fast_length: u16,
+    slow_length: u16,
+    signal_length: u16,
+) -> Vec<MacdValues> {
+    let close: Vec<Option<f64>> = price_data.iter().map(|hlc| Some(hlc.price_close)).collect();
+
+    let fast_ema = ema_for_ticks(&close, fast_length);
+    let slow_ema = ema_for_ticks(&close, slow_length);
+
+    let macd_line: Vec<Option<f64>> = fast_ema
+        .into_iter()
+        .zip(slow_ema)
+        .map(|(fast, slow)| Some(fast? - slow?))
+        .collect();
+    let signal_line = ema_for_ticks(&macd_line, signal_length);
+
+    macd_line
+        .into_iter()
+        .zip(signal_line)
+        .map(|(macd, signal)| MacdValues {
+            macd_line: macd,
+            signal_line: signal,
+            histogram: macd.zip(signal).map(|(m, s)| m - s),
+        })
+        .collect()
+}
@@ -10,6 +10,10 @@
 //!
 //! # Sub-modules:
 //! - `models`: Contains data structures and models required for oscillator calculations, such as [`models::Hlc`].
+//! - `atr`: Provides the Average True Range (ATR) calculation using Wilder's smoothing.
+//! - `ema`: Provides functions for calculating the Exponential Moving Average (EMA) of price data.
+//! - `macd`: Offers the Moving Average Convergence Divergence (MACD), its signal line and histogram.
+//! - `rsi`: Provides the Relative Strength Index (RSI) calculation using Wilder's smoothing.
 //! - `sma`: Provides functions for calculating the Simple Moving Average (SMA) of price data.
 //! - `stochastic`: Offers functionalities related to the Stochastic Oscillator, including the raw stochastic value
 //!   calculation (%K), and the smoothed stochastic value (%D).
@@ -17,6 +21,10 @@
 //! Depending on the specific oscillator you're interested in, you might then dive deeper into one of the sub-modules
 //! to use its functions or structures.
 
+pub mod atr;
+pub mod ema;
+pub mod macd;
 pub mod models;
+pub mod rsi;
 pub mod sma;
 pub mod stochastic;
@@ -0,0 +1,72 @@
+use crate::oscillators::models::Hlc;
+
+/// Calculates the Relative Strength Index (RSI) for a slice of price data.
+///
+/// The close-to-close changes of `price_close` are split into gains and losses, which are then smoothed
+/// with Wilder's moving average (also known as RMA). The first average is a simple mean over the first
+/// `length` changes, every following average is `(previous * (length - 1) + current) / length`.
+///
+/// # Arguments
+/// * `price_data` - A slice of `Hlc` structs representing the price data.
+/// * `length` - The number of periods used for the Wilder smoothing of gains and losses.
+///
+/// # Returns
+/// A vector of `Option<f64>`, each containing the RSI (0 to 100) for the corresponding tick,
+/// or `None` for the first `length` ticks where there is insufficient data.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::oscillators::{models::Hlc, rsi::rsi_for_ticks};
+///
+/// let price_data = vec![
+///     Hlc::new(1.0, 1.0, 1.0),
+///     Hlc::new(2.0, 2.0, 2.0),
+///     Hlc::new(3.0, 3.0, 3.0),
+///     Hlc::new(2.0, 2.0, 2.0),
+///     Hlc::new(3.0, 3.0, 3.0),
+/// ];
+///
+/// assert_eq!(rsi_for_ticks(&price_data, 2), vec![None, None, Some(100.0), Some(50.0), Some(75.0)]);
+/// ```
+pub fn rsi_for_ticks(price_data: &[Hlc], length: u16) -> Vec<Option<f64>> {
+    let length = length as usize;
+    let mut res = vec![None; price_data.len()];
+
+    if length == 0 || price_data.len() <= length {
+        return res;
+    }
+
+    let mut avg_gain = 0.0;
+    let mut avg_loss = 0.0;
+
+    for ix in 1..price_data.len() {
+        let change = price_data[ix].price_close - price_data[ix - 1].price_close;
+        let (gain, loss) = if change > 0.0 { (change, 0.0) } else { (0.0, -change) };
+
+        if ix <= length {
+            // Seed the averages with the simple mean of the first `length` changes
+            avg_gain += gain / length as f64;
+            avg_loss += loss / length as f64;
+            if ix < length { continue; }
+        } else {
+            avg_gain = (avg_gain * (length - 1) as f64 + gain) / length as f64;
+            avg_loss = (avg_loss * (length - 1) as f64 + loss) / length as f64;
+        }
+
+        res[ix] = Some(rsi_value(avg_gain, avg_loss));
+    }
+    res
+}
+
+/// Converts the smoothed average gain and loss into an RSI value.
+///
+/// Follows the common charting convention: no losses yields 100, no gains yields 0.
+fn rsi_value(avg_gain: f64, avg_loss: f64) -> f64 {
+    if avg_loss == 0.0 {
+        100.0
+    } else if avg_gain == 0.0 {
+        0.0
+    } else {
+        100.0 - 100.0 / (1.0 + avg_gain / avg_loss)
+    }
+}
@@ -36,6 +36,8 @@ pub fn sma_for_tick(data: &[Option<f64>], index: usize, period: u16) -> Option<f
 ///
 /// This function implements O(n) time complexity by maintaining a running sum for the current period,
 /// and as it moves forward in the data, it subtracts the first element going out of the period and adds the next element coming in.
+/// The running sum is compensated (Neumaier) and summed afresh once rolled forward `period` times, so rounding errors do not
+/// accumulate over long series, nor linger once a value far apart in magnitude leaves the period.
 ///
 /// # Arguments
 /// * `data` - A vector of `Option<f64>` values for which the SMA should be calculated. Each `Option<f64>` represents a possible price at a given tick.
@@ -43,7 +45,7 @@ pub fn sma_for_tick(data: &[Option<f64>], index: usize, period: u16) -> Option<f
 ///
 /// # Returns
 /// * `Vec<Option<f64>>` - A vector where each element is an Option that holds the SMA of the `period` elements in `data` preceding it,
-///                        or None if there's not enough preceding data to compute an SMA or if any data point in the period is `None`.
+///   or None if there's not enough preceding data to compute an SMA or if any data point in the period is `None`.
 ///
 /// # Examples
 /// ```
@@ -54,21 +56,70 @@ pub fn sma_for_tick(data: &[Option<f64>], index: usize, period: u16) -> Option<f
 /// let sma = sma_for_ticks(&data, period);
 ///
 /// assert_eq!(sma, vec![None, None, Some(2.0), Some(3.0), Some(4.0), Some(5.0)]);
+///
+/// // Periods containing a `None` are `None` as well
+/// let data = vec![None, Some(1.0), Some(2.0), Some(3.0), None, Some(4.0), Some(5.0), Some(6.0)];
+/// let sma = sma_for_ticks(&data, 2);
+///
+/// assert_eq!(sma, vec![None, None, Some(1.5), Some(2.5), None, None, Some(4.5), Some(5.5)]);
+///
+/// // No drift once a large value leaves the period: a plain running sum loses the 1.0s added next to 1e17
+/// // and would yield 0.0 from index 3 on
+/// let data = vec![Some(1e17), Some(1.0), Some(1.0), Some(1.0), Some(1.0)];
+/// let sma = sma_for_ticks(&data, 3);
+///
+/// assert_eq!(sma[3..], [Some(1.0), Some(1.0)]);
 /// ```
 pub fn sma_for_ticks(data: &[Option<f64>], period: u16) -> Vec<Option<f64>> {
-    let data_len: usize = data.len();
-    let mut res = vec![None; data_len];
+    let period = period as usize;
+    let mut res = vec![None; data.len()];
+    if period == 0 { return res; }
+
+    let mut sum = 0.0;
+    // Low-order bits lost by the running sum
+    let mut compensation = 0.0;
+    // Number of consecutive `Some` values up to the current tick
+    let mut run: usize = 0;
+    // Number of ticks rolled forward since the period was last summed afresh
+    let mut rolled: usize = 0;
+
+    for (ix, value) in data.iter().enumerate() {
+        let Some(value) = *value else {
+            // A `None` invalidates every period containing it, start over after it
+            sum = 0.0;
+            compensation = 0.0;
+            run = 0;
+            rolled = 0;
+            continue;
+        };
 
-    for ix in 0..data_len {
-        if ix >= period as usize - 1 {
-            let slice = &data[ix + 1 - period as usize..=ix];
-            // Skip if slice contains None
-            if slice.iter().any(|&x| x.is_none()) {
-                continue;
+        run += 1;
+
+        if run < period {
+            add_compensated(&mut sum, &mut compensation, value);
+        } else if run == period || rolled == period {
+            // Within an unbroken run, so every value of the period is `Some`
+            sum = 0.0;
+            compensation = 0.0;
+            for value in &data[ix + 1 - period..=ix] {
+                add_compensated(&mut sum, &mut compensation, value.unwrap_or_default());
             }
-            let sum: f64 = slice.iter().filter_map(|&x| x).sum();
-            res[ix] = Some(sum / period as f64);
+            rolled = 0;
+        } else {
+            add_compensated(&mut sum, &mut compensation, value);
+            add_compensated(&mut sum, &mut compensation, -data[ix - period].unwrap_or_default());
+            rolled += 1;
+        }
+        if run >= period {
+            res[ix] = Some((sum + compensation) / period as f64);
         }
     }
     res
 }
+
+/// Adds `value` to `sum`, accumulating the rounding error in `compensation` (Neumaier summation).
+fn add_compensated(sum: &mut f64, compensation: &mut f64, value: f64) {
+    let total = *sum + value;
+    *compensation += if sum.abs() >= value.abs() { (*sum - total) + value } else { (value - total) + *sum };
+    *sum = total;
+}
@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+
 use crate::oscillators::{
     models::Hlc,
     sma::{sma_for_tick, sma_for_ticks},
@@ -60,6 +62,10 @@ pub fn k_for_tick(price_data: &[Hlc], index: usize, k_length: u16) -> Option<f64
 
 /// Calculates the raw stochastic value (%K) for a slice of price data.
 ///
+/// The lowest low and highest high of the lookback window are tracked with monotonic deques of tick indices,
+/// so each tick is pushed and popped at most once and the whole calculation is O(n) regardless of `k_length`.
+/// The result is identical to calling [`k_for_tick`] for every index.
+///
 /// # Arguments
 /// * `price_data` - A slice of `Hlc` structs representing the price data.
 /// * `k_length` - The lookback period length over which to calculate the %K.
@@ -85,11 +91,35 @@ pub fn k_for_tick(price_data: &[Hlc], index: usize, k_length: u16) -> Option<f64
 /// assert_eq!(k_for_ticks(&price_data, 3), vec![None, None, Some(83.33333333333331)]);
 /// ```
 pub fn k_for_ticks(price_data: &[Hlc], k_length: u16) -> Vec<Option<f64>> {
-    let result = price_data
-        .iter()
-        .enumerate()
-        .map(|(i, ..)| k_for_tick(price_data, i, k_length))
-        .collect();
+    let k_length = k_length as usize;
+    let mut result = vec![None; price_data.len()];
+    if k_length == 0 { return result; }
+
+    // Indices of candidate lows (increasing prices) and highs (decreasing prices) within the window
+    let mut lows: VecDeque<usize> = VecDeque::with_capacity(k_length);
+    let mut highs: VecDeque<usize> = VecDeque::with_capacity(k_length);
+
+    for (index, hlc) in price_data.iter().enumerate() {
+        while lows.back().is_some_and(|&ix| price_data[ix].price_low >= hlc.price_low) { lows.pop_back(); }
+        lows.push_back(index);
+
+        while highs.back().is_some_and(|&ix| price_data[ix].price_high <= hlc.price_high) { highs.pop_back(); }
+        highs.push_back(index);
+
+        if index + 1 < k_length { continue; }
+
+        // Drop indices which fell out of the lookback window
+        let window_start = index + 1 - k_length;
+        while lows.front().is_some_and(|&ix| ix < window_start) { lows.pop_front(); }
+        while highs.front().is_some_and(|&ix| ix < window_start) { highs.pop_front(); }
+
+        let low_val = price_data[lows[0]].price_low;
+        let high_val = price_data[highs[0]].price_high;
+
+        if high_val - low_val != 0.0 {
+            result[index] = Some(100.0 * (hlc.price_close - low_val) / (high_val - low_val));
+        }
+    }
     result
 }
 
@@ -121,6 +151,9 @@ pub fn d_for_tick(k_values: &[Option<f64>], index: usize, d_length: u16) -> Opti
 }
 
 /// Calculates the Simple Moving Average (SMA) of %K values for a slice of price data.
+///
+/// Uses the rolling sum of [`sma_for_ticks`], matching [`d_for_tick`] for every index up to floating point rounding.
+///
 /// # Arguments
 /// * `k_values` - A slice of `Option<f64>` representing the %K values.
 /// * `d_length` - The period length over which to calculate the SMA.
@@ -140,13 +173,7 @@ pub fn d_for_tick(k_values: &[Option<f64>], index: usize, d_length: u16) -> Opti
 /// assert_eq!(d_values, vec![None, None, Some(20.0), Some(30.0), Some(40.0), Some(50.0)]);
 /// ```
 pub fn d_for_ticks(k_values: &[Option<f64>], d_length: u16) -> Vec<Option<f64>> {
-    let result = k_values
-        .iter()
-        .enumerate()
-        .map(|(i, ..)| d_for_tick(k_values, i, d_length))
-        .collect();
-
-    result
+    sma_for_ticks(k_values, d_length)
 }
 
 /// Represents the Stochastic Oscillator values at a single tick.
@@ -237,7 +264,7 @@ pub fn stochastic(
 
     k_line
         .into_iter()
-        .zip(d_line.into_iter())
+        .zip(d_line)
         .map(|(k, d)| StochValues {
             k_line: k,
             d_line: d,
@@ -0,0 +1,75 @@
+//! Numeric types the PnL simulation can be carried out in.
+//!
+//! [`Decimal`] is the precise representation, truncating every trade to the wallet and exchange precision.
+//! [`f64`] is the fast representation used to screen large parameter grids, truncation is skipped entirely.
+use std::{
+    fmt::Debug,
+    ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign},
+};
+
+use rust_decimal::prelude::*;
+
+/// Arithmetic required by the simulator, implemented for [`Decimal`] and [`f64`].
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::amount::Amount;
+/// use rust_decimal::Decimal;
+///
+/// assert_eq!(Decimal::from_f64_value(1.23456).trunc_with_scale(2).to_f64_value(), 1.23);
+/// assert_eq!(f64::from_f64_value(1.23456).trunc_with_scale(2), 1.23456);
+/// assert_eq!(1.23456f64.round_dp(2), 1.23);
+/// ```
+pub trait Amount:
+    Copy + Debug + PartialOrd + Send + Sync
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self> + Neg<Output = Self>
+    + AddAssign + SubAssign
+{
+    const ZERO: Self;
+
+    /// Converts from `f64`, panics if the value is not representable.
+    fn from_f64_value(value: f64) -> Self;
+
+    /// Converts into `f64`.
+    fn to_f64_value(self) -> f64;
+
+    /// Truncates to `scale` decimal places, a no-op for the fast representation.
+    fn trunc_with_scale(self, scale: u32) -> Self;
+
+    /// Rounds to `dp` decimal places.
+    fn round_dp(self, dp: u32) -> Self;
+
+    /// Returns the absolute value.
+    fn abs(self) -> Self;
+}
+
+impl Amount for Decimal {
+    const ZERO: Self = Decimal::ZERO;
+
+    fn from_f64_value(value: f64) -> Self { Decimal::from_f64(value).unwrap() }
+
+    fn to_f64_value(self) -> f64 { self.to_f64().unwrap() }
+
+    fn trunc_with_scale(self, scale: u32) -> Self { Decimal::trunc_with_scale(&self, scale) }
+
+    fn round_dp(self, dp: u32) -> Self { Decimal::round_dp(&self, dp) }
+
+    fn abs(self) -> Self { Decimal::abs(&self) }
+}
+
+impl Amount for f64 {
+    const ZERO: Self = 0.0;
+
+    fn from_f64_value(value: f64) -> Self { value }
+
+    fn to_f64_value(self) -> f64 { self }
+
+    fn trunc_with_scale(self, _scale: u32) -> Self { self }
+
+    fn round_dp(self, dp: u32) -> Self {
+        let factor = 10f64.powi(dp as i32);
+        (self * factor).round() / factor
+    }
+
+    fn abs(self) -> Self { f64::abs(self) }
+}
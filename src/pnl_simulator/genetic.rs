@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+
+use super::{
+    optimizer::{Optimizer, Profit},
+    search::{Rng, SearchSpace, MAX_ATTEMPTS},
+};
+
+/// Settings of the genetic search of [`Optimizer::genetic`].
+///
+/// # Fields
+/// - `population`      : Default (50). The number of configurations of each generation.
+/// - `generations`     : Default (30). The number of generations bred after the initial random population.
+/// - `crossover_rate`  : Default (0.9). The probability of a child mixing the parameters of two parents, a copy of the first otherwise.
+/// - `mutation_rate`   : Default (0.1). The probability of each parameter of a child being redrawn within its range.
+/// - `elitism`         : Default (2). The number of best configurations carried over to the next generation unchanged.
+/// - `tournament`      : Default (3). The number of configurations competing for each parent slot.
+/// - `seed`            : Default (0). Seeds the random number generator, the same seed yields the same search.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::genetic::Genetic;
+///
+/// let genetic = Genetic { population: 100, seed: 42, ..Default::default() };
+///
+/// assert_eq!(genetic.generations, 30);
+/// assert_eq!(genetic.elitism, 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Genetic {
+    pub population      : usize,
+    pub generations     : usize,
+    pub crossover_rate  : f64,
+    pub mutation_rate   : f64,
+    pub elitism         : usize,
+    pub tournament      : usize,
+    pub seed            : u64,
+}
+
+impl Default for Genetic {
+    fn default() -> Self {
+        Genetic {
+            population      : 50,
+            generations     : 30,
+            crossover_rate  : 0.9,
+            mutation_rate   : 0.1,
+            elitism         : 2,
+            tournament      : 3,
+            seed            : 0,
+        }
+    }
+}
+
+impl Genetic {
+    /// Evolves populations of `range` by the scores of `score`, greater values being better, see [`Optimizer::genetic`].
+    ///
+    /// Every generation, `score` is called once with the configurations of the population not scored before, each one once,
+    /// and returns their scores, `None` for a configuration failing the constraints. Unscored configurations rank below all others.
+    ///
+    /// # Returns
+    /// - Every generation with the scores of its configurations, best first, starting with the initial population.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use oscillatorsetups::pnl_simulator::genetic::Genetic;
+    /// use oscillatorsetups::pnl_simulator::search::SearchSpace;
+    /// use oscillatorsetups::pnl_simulator::stochastic::{PnlParams, PnlRange};
+    ///
+    /// let range = PnlRange { k_length: 3..=200, k_smoothing: 3..=50, d_length: 3..=50 };
+    /// let genetic = Genetic { population: 20, generations: 15, seed: 7, ..Default::default() };
+    ///
+    /// // A score peaking at k_length 40, k_smoothing 10, d_length 5, recording every configuration scored
+    /// let mut scored = Vec::new();
+    /// let mut score = |setups: Vec<PnlParams>| -> BTreeMap<PnlParams, Option<f64>> {
+    ///     scored.extend(setups.iter().cloned());
+    ///     setups.into_iter().map(|setup| {
+    ///         let distance: f64 = setup.params().iter().zip([40.0, 10.0, 5.0]).map(|(&value, peak)| (value as f64 - peak).abs()).sum();
+    ///         (setup, Some(-distance))
+    ///     }).collect()
+    /// };
+    /// let generations = genetic.evolve(&range, &mut score);
+    /// assert_eq!(generations.len(), 16);
+    ///
+    /// // No configuration is scored twice
+    /// let mut unique = scored.clone();
+    /// unique.sort();
+    /// unique.dedup();
+    /// assert_eq!(unique.len(), scored.len());
+    ///
+    /// // Every configuration lies within the range
+    /// assert!(scored.iter().all(|setup| (3..=200).contains(&setup.k_length) && (3..=50).contains(&setup.d_length)));
+    ///
+    /// // The elite carries the best configuration over, so the best score never drops
+    /// let best: Vec<f64> = generations.iter().map(|generation| generation[0].1.unwrap()).collect();
+    /// assert!(best.windows(2).all(|pair| pair[0] <= pair[1]));
+    ///
+    /// // The same seed evolves the same generations
+    /// let mut again = |setups: Vec<PnlParams>| -> BTreeMap<PnlParams, Option<f64>> {
+    ///     setups.into_iter().map(|setup| (setup.clone(), Some(-(setup.k_length as f64 - 40.0).abs()))).collect()
+    /// };
+    /// assert_eq!(genetic.evolve(&range, &mut again), genetic.evolve(&range, &mut again));
+    /// ```
+    pub fn evolve<S: SearchSpace>(&self, range: &S::Range, mut score: impl FnMut(Vec<S>) -> BTreeMap<S, Option<f64>>) -> Vec<Vec<(S, Option<f64>)>> {
+        let bounds = S::bounds(range);
+        if bounds.iter().any(|bounds| bounds.is_empty()) { return Vec::new(); }
+
+        let mut rng = Rng::new(self.seed);
+        let mut evaluated: BTreeMap<S, Option<f64>> = BTreeMap::new();
+        let mut generations = Vec::new();
+
+        let mut population: Vec<S> = (0..self.population * MAX_ATTEMPTS)
+            .filter_map(|_| S::from_params(&bounds.iter().map(|bounds| rng.within(bounds)).collect::<Vec<u16>>()))
+            .take(self.population)
+            .collect();
+
+        for generation in 0..=self.generations {
+            let mut pending: Vec<S> = population.iter().filter(|setup| !evaluated.contains_key(setup)).cloned().collect();
+            pending.sort();
+            pending.dedup();
+            if !pending.is_empty() { evaluated.extend(score(pending)); }
+
+            let fitness = |setup: &S| evaluated.get(setup).copied().flatten().unwrap_or(f64::NEG_INFINITY);
+            population.sort_by(|a, b| fitness(b).total_cmp(&fitness(a)).then_with(|| a.cmp(b)));
+            generations.push(population.iter().map(|setup| (setup.clone(), evaluated.get(setup).copied().flatten())).collect());
+
+            if generation == self.generations || population.is_empty() { break; }
+
+            let mut next: Vec<S> = population.iter().take(self.elitism).cloned().collect();
+
+            while next.len() < self.population {
+                let first = tournament(&population, &fitness, self.tournament, &mut rng);
+                let second = tournament(&population, &fitness, self.tournament, &mut rng);
+                let crossover = rng.unit() < self.crossover_rate;
+
+                let child = (0..MAX_ATTEMPTS)
+                    .find_map(|_| {
+                        let params: Vec<u16> = first.params().into_iter()
+                            .zip(second.params())
+                            .zip(&bounds)
+                            .map(|((a, b), bounds)| {
+                                let gene = if crossover && rng.unit() < 0.5 { b } else { a };
+                                if rng.unit() < self.mutation_rate { rng.within(bounds) } else { gene }
+                            })
+                            .collect();
+                        S::from_params(&params)
+                    })
+                    .unwrap_or_else(|| first.clone());
+
+                next.push(child);
+            }
+            population = next;
+        }
+
+        generations
+    }
+}
+
+impl<S: SearchSpace> Optimizer<'_, S> {
+    /// Searches `range` with a genetic algorithm, simulating only a fraction of the configurations [`Optimizer::top_net_profit`] would.
+    ///
+    /// The initial population is drawn at random from `range`. Every generation keeps its `elitism` best configurations,
+    /// and breeds the rest from parents picked by tournament: uniform crossover of their parameters, then mutation.
+    /// Each configuration is scored once by the `objective` and `constraints` of this instance, the ones failing the constraints
+    /// rank below all others. The configurations of a generation are simulated in parallel. See [`Genetic::evolve`].
+    ///
+    /// # Parameters
+    /// - `range`: The ranges of parameters to search, e.g. [`crate::pnl_simulator::stochastic::PnlRange`].
+    /// - `genetic`: The [`Genetic`] settings.
+    ///
+    /// # Returns
+    /// - The top `top_n` configurations of all generations with their score, in ascending order as [`Optimizer::top_net_profit`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oscillatorsetups::exchange::chart_data::klines::{Intervals, KlinesSubset};
+    /// use oscillatorsetups::pnl_simulator::genetic::Genetic;
+    /// use oscillatorsetups::pnl_simulator::stochastic::{PnlRange, Stochastic};
+    ///
+    /// // A 12 kline cycle, too short a history for the 1.3 million configurations of the range to be simulated in a test
+    /// let klines: Vec<KlinesSubset> = (0..300u64).map(|ix| {
+    ///     let price = 100.0 + 10.0 * (ix as f64 * std::f64::consts::TAU / 12.0).sin();
+    ///     KlinesSubset { time_open: ix * 3_600_000, price_open: price, price_low: price - 1.0, price_high: price + 1.0, price_close: price, time_close: ix * 3_600_000 + 3_599_999, volume: 1.0 }
+    /// }).collect();
+    /// let stochastic = Stochastic::from_klines("binance", klines, Intervals::H1).top_n(10);
+    ///
+    /// let range = || PnlRange { k_length: 3..=200, k_smoothing: 3..=80, d_length: 3..=80 };
+    /// let genetic = Genetic { population: 30, generations: 10, seed: 42, ..Default::default() };
+    ///
+    /// let top_profits = stochastic.genetic(range(), genetic.clone());
+    /// assert_eq!(top_profits.len(), 10);
+    /// assert!(top_profits.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    /// assert_eq!(top_profits, stochastic.genetic(range(), genetic));
+    /// ```
+    pub fn genetic(&self, range: S::Range, genetic: Genetic) -> Vec<(Profit, S)> {
+        let generations = genetic.evolve(&range, |setups| {
+            let mut scores = BTreeMap::new();
+            self.evaluate(setups, &mut scores);
+            scores
+        });
+        let evaluated: BTreeMap<S, Option<f64>> = generations.into_iter().flatten().collect();
+
+        self.top_evaluated(&evaluated)
+    }
+}
+
+/// Picks the fittest of `size` configurations drawn at random from `population`.
+fn tournament<'p, S>(population: &'p [S], fitness: &impl Fn(&S) -> f64, size: usize, rng: &mut Rng) -> &'p S {
+    (0..size.max(1))
+        .map(|_| &population[rng.below(population.len())])
+        .max_by(|a, b| fitness(a).total_cmp(&fitness(b)))
+        .unwrap()
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+
+use crate::exchange::chart_data::klines::KlinesSubset;
+use super::{
+    models::PnL,
+    optimizer::{OscillatorSetup, Optimizer, Profit},
+};
+
+use rayon::prelude::*;
+
+/// Where the klines are split into a training and a held-out portion, see [`Optimizer::holdout`].
+///
+/// # Variants
+/// - `Fraction`: The share of klines, counted from the oldest, used for training, e.g. `0.7` for 70%.
+/// - `Timestamp`: The first kline opening at or after this time in milliseconds is the first held-out kline.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::exchange::chart_data::klines::KlinesSubset;
+/// use oscillatorsetups::pnl_simulator::holdout::Split;
+///
+/// let klines: Vec<KlinesSubset> = (0..10).map(|ix| KlinesSubset {
+///     time_open: ix * 60_000, price_open: 10.0, price_low: 9.0, price_high: 11.0,
+///     price_close: 10.0, time_close: ix * 60_000 + 59_999, volume: 1.0,
+/// }).collect();
+///
+/// assert_eq!(Split::Fraction(0.7).index(&klines), 7);
+/// assert_eq!(Split::Timestamp(150_000).index(&klines), 3);
+/// assert_eq!(Split::Fraction(1.5).index(&klines), 10);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Split {
+    Fraction(f64),
+    Timestamp(u64),
+}
+
+impl Split {
+    /// Returns the index of the first held-out kline of `klines`, `klines.len()` if nothing is held out.
+    pub fn index(&self, klines: &[KlinesSubset]) -> usize {
+        match *self {
+            Split::Fraction(fraction)   => (klines.len() as f64 * fraction.clamp(0.0, 1.0)).round() as usize,
+            Split::Timestamp(time)      => klines.partition_point(|kline| kline.time_open < time),
+        }
+    }
+}
+
+/// A top configuration of [`Optimizer::holdout`], with its performance on both portions of the klines.
+///
+/// # Fields
+/// - `score`           : The score of the configuration on the training portion, the one it was ranked by.
+/// - `setup`           : The configuration.
+/// - `in_sample`       : The [`PnL`] of `setup` on the training portion.
+/// - `out_of_sample`   : The [`PnL`] of `setup` on the held-out portion, starting flat with the full capital.
+///
+/// The [`fmt::Display`] implementation lays out the key metrics of both portions side by side.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::{holdout::HoldoutResult, models::PnL, optimizer::Profit};
+///
+/// let result = HoldoutResult {
+///     score           : Profit(120.0),
+///     setup           : "k14",
+///     in_sample       : PnL { net_profit: 120.0, total_closed_trades: 40, ..Default::default() },
+///     out_of_sample   : PnL { net_profit: -15.5, total_closed_trades: 12, ..Default::default() },
+/// };
+///
+/// assert_eq!(
+///     result.to_string(),
+///     "\"k14\": net profit 120 / -15.5, profit factor 0 / 0, percent profitable 0 / 0, max drawdown % 0 / 0, closed trades 40 / 12",
+/// );
+/// ```
+#[derive(Debug)]
+pub struct HoldoutResult<S> {
+    pub score           : Profit,
+    pub setup           : S,
+    pub in_sample       : PnL,
+    pub out_of_sample   : PnL,
+}
+
+impl<S: fmt::Debug> fmt::Display for HoldoutResult<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (is, oos) = (&self.in_sample, &self.out_of_sample);
+        write!(
+            f,
+            "{:?}: net profit {} / {}, profit factor {} / {}, percent profitable {} / {}, max drawdown % {} / {}, closed trades {} / {}",
+            self.setup,
+            is.net_profit, oos.net_profit,
+            is.profit_factor, oos.profit_factor,
+            is.percent_profitable, oos.percent_profitable,
+            is.max_drawdown_percent, oos.max_drawdown_percent,
+            is.total_closed_trades, oos.total_closed_trades,
+        )
+    }
+}
+
+impl<S: OscillatorSetup> Optimizer<'_, S> {
+    /// Ranks `range` on the training portion of the klines, reporting each top configuration on the held-out portion as well.
+    ///
+    /// The training portion is ranked as in [`Optimizer::top_net_profit`], by the `objective` and `constraints` of this instance.
+    /// The held-out klines never influence the ranking, so a configuration degrading out-of-sample was fit to noise.
+    /// The signal lines are calculated over all klines, so the training klines serve as the warm-up of the held-out portion.
+    ///
+    /// # Parameters
+    /// - `range`: The ranges of parameters to optimize, e.g. [`crate::pnl_simulator::stochastic::PnlRange`].
+    /// - `split`: The [`Split`] between the training and the held-out klines.
+    ///
+    /// # Returns
+    /// - The top `top_n` configurations as [`HoldoutResult`]s, in ascending order of their training score.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oscillatorsetups::exchange::chart_data::klines::{Intervals, KlinesSubset};
+    /// use oscillatorsetups::pnl_simulator::holdout::Split;
+    /// use oscillatorsetups::pnl_simulator::stochastic::{PnlRange, Stochastic};
+    ///
+    /// // A 12 kline cycle for training, followed by a steady decline held out
+    /// let klines: Vec<KlinesSubset> = (0..200u64).map(|ix| {
+    ///     let price = if ix < 140 { 100.0 + 10.0 * (ix as f64 * std::f64::consts::TAU / 12.0).sin() } else { 100.0 - (ix - 140) as f64 * 0.5 };
+    ///     KlinesSubset { time_open: ix * 3_600_000, price_open: price, price_low: price - 1.0, price_high: price + 1.0, price_close: price, time_close: ix * 3_600_000 + 3_599_999, volume: 1.0 }
+    /// }).collect();
+    /// let range = || PnlRange { k_length: 5..=20, k_smoothing: 3..=5, d_length: 3..=5 };
+    ///
+    /// let results = Stochastic::from_klines("binance", klines.clone(), Intervals::H1).holdout(range(), Split::Fraction(0.7));
+    /// let best = results.last().unwrap();
+    ///
+    /// // Ranked as if the held-out klines did not exist
+    /// let (score, setup) = Stochastic::from_klines("binance", klines[..140].to_vec(), Intervals::H1).top_net_profit(range()).pop().unwrap();
+    /// assert_eq!((&best.score, &best.setup), (&score, &setup));
+    /// assert_eq!(best.in_sample.net_profit, score.0);
+    ///
+    /// // Traded on the held-out klines only, from the open of the first to the close of the last one
+    /// let held_out = 1000.0 * klines[199].price_close / klines[140].price_open - 1000.0;
+    /// assert!((best.out_of_sample.buy_and_hold_return - held_out).abs() < 0.01);
+    ///
+    /// // Profitable in training, losing on the held-out decline
+    /// assert_eq!(
+    ///     best.to_string(),
+    ///     "PnlParams { k_length: 7, k_smoothing: 3, d_length: 3 }: net profit 2006.9252463 / -25.25252525, profit factor 0 / 0, \
+    ///      percent profitable 100 / 0, max drawdown % 4.55 / 2.53, closed trades 11 / 1",
+    /// );
+    /// ```
+    pub fn holdout(&self, range: S::Range, split: Split) -> Vec<HoldoutResult<S>> {
+        let split = split.index(&self.klines);
+        let (train, test) = (0..split, split..self.klines.len());
+
+        let top_profits = self.rank(&range, std::slice::from_ref(&train)).pop().unwrap_or_default();
+
+        top_profits
+            .into_par_iter()
+            .map(|(score, setup)| HoldoutResult {
+                score,
+                in_sample       : self.pnl_within(setup.clone(), train.clone()),
+                out_of_sample   : self.pnl_within(setup.clone(), test.clone()),
+                setup,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    cmp::Ordering,
+    ops::RangeInclusive,
+};
+
+use crate::oscillators::{models::Hlc, macd::macd};
+use super::{
+    optimizer::{OscillatorSetup, Optimizer},
+    search::SearchSpace,
+};
+
+/// `MacdParams` represents the configuration parameters used for Profit and Loss (PnL) simulations
+/// when utilizing the Moving Average Convergence Divergence. The MACD line crossing above the signal line
+/// triggers an entry, crossing back below it triggers an exit.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct MacdParams {
+    /// `fast_length` denotes the period of the fast EMA of the closing price.
+    pub fast_length     : u16,
+    /// `slow_length` denotes the period of the slow EMA of the closing price. Expected to be greater than `fast_length`.
+    pub slow_length     : u16,
+    /// `signal_length` denotes the period of the EMA applied to the MACD line, producing the signal line.
+    pub signal_length   : u16,
+}
+impl PartialOrd for MacdParams {
+    /// Provides a mechanism to compare two [`MacdParams`], see [`MacdParams::cmp`].
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for MacdParams {
+    /// Compares two [`MacdParams`] for ordering. The comparison starts with `fast_length`, followed by
+    /// `slow_length`, and then `signal_length`.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.fast_length.cmp(&other.fast_length)
+            .then_with(|| self.slow_length.cmp(&other.slow_length))
+            .then_with(|| self.signal_length.cmp(&other.signal_length))
+    }
+}
+
+/// Defines the range of parameters for the MACD used in the PnL simulations.
+/// # Example
+/// ```
+/// use oscillatorsetups::pnl_simulator::macd::MacdRange;
+///
+/// let macd_range = MacdRange {
+///     fast_length     : 5..=20,
+///     slow_length     : 20..=50,
+///     signal_length   : 3..=15,
+/// };
+///
+/// assert_eq!(macd_range.fast_length   , 5..=20);
+/// assert_eq!(macd_range.slow_length   , 20..=50);
+/// assert_eq!(macd_range.signal_length , 3..=15);
+/// ```
+#[derive(Debug)]
+pub struct MacdRange {
+    /// The inclusive range for fast_length.
+    pub fast_length     : RangeInclusive<u16>,
+    /// The inclusive range for slow_length.
+    pub slow_length     : RangeInclusive<u16>,
+    /// The inclusive range for signal_length.
+    pub signal_length   : RangeInclusive<u16>,
+}
+
+impl OscillatorSetup for MacdParams {
+    type Range = MacdRange;
+
+    /// Expands [`MacdRange`] into every combination where `fast_length` is below `slow_length`.
+    fn configurations(range: &MacdRange) -> Vec<Self> {
+        range.fast_length.clone().flat_map(|fast_length| {
+            range.slow_length.clone()
+                .filter(move |&slow_length| fast_length < slow_length)
+                .flat_map(move |slow_length| {
+                    range.signal_length.clone().map(move |signal_length| MacdParams { fast_length, slow_length, signal_length })
+                })
+        }).collect()
+    }
+
+    /// Uses the MACD line as `signal_in` and the signal line as `signal_out`.
+    fn signal_lines(&self, price_data: &[Hlc]) -> (Vec<Option<f64>>, Vec<Option<f64>>) {
+        macd(price_data, self.fast_length, self.slow_length, self.signal_length)
+            .into_iter()
+            .map(|value| (value.macd_line, value.signal_line))
+            .unzip()
+    }
+}
+
+impl SearchSpace for MacdParams {
+    /// Bounds `fast_length`, `slow_length` and `signal_length`, in this order.
+    fn bounds(range: &MacdRange) -> Vec<RangeInclusive<u16>> {
+        vec![range.fast_length.clone(), range.slow_length.clone(), range.signal_length.clone()]
+    }
+
+    /// Leaves out the combinations where `fast_length` is not below `slow_length`, as [`MacdParams::configurations`].
+    fn from_params(params: &[u16]) -> Option<Self> {
+        match *params {
+            [fast_length, slow_length, signal_length] if fast_length < slow_length => Some(MacdParams { fast_length, slow_length, signal_length }),
+            _ => None,
+        }
+    }
+
+    fn params(&self) -> Vec<u16> {
+        vec![self.fast_length, self.slow_length, self.signal_length]
+    }
+}
+
+/// Represents a MACD simulation for a given financial exchange.
+/// The Moving Average Convergence Divergence is a trend-following momentum indicator showing the relationship
+/// between two exponential moving averages of the price.
+///
+/// See [`Optimizer`] for the available settings and methods, e.g. [`Optimizer::pnl`] and [`Optimizer::top_net_profit`].
+pub type Macd<'a> = Optimizer<'a, MacdParams>;
@@ -1,23 +1,70 @@
 //! The `pnl_simulator` module provides functionality to simulate and analyze profit and loss based
 //! on various parameters and algorithms, specifically focusing on stochastic operations.
 
+//! The `amount` module contains the numeric abstraction the simulation is carried out in,
+//! precise `Decimal` amounts or fast `f64` amounts.
+
+//! The `genetic` module contains the genetic optimizer, evolving a population of configurations
+//! by crossover, mutation and elitism instead of simulating every configuration of a range.
+
+//! The `holdout` module contains the train/test split, ranking configurations on the training
+//! klines and reporting their performance on the held-out klines side by side.
+
+//! The `macd` module contains the logic related to the Moving Average Convergence Divergence
+//! and its utilization in the PnL simulations, using MACD line / signal line crossovers
+//! while sweeping the fast, slow and signal lengths.
+
 //! The `models` module contains the necessary data structures to support
 //! the PnL simulations and stochastic operations.
 //!
 //! This might include representations for candles, signals, PnL results,
 //! and any other related entities.
 
+//! The `objective` module contains the measures configurations can be ranked by,
+//! and the constraints a configuration has to satisfy to be ranked at all.
+
+//! The `optimizer` module contains the [`optimizer::OscillatorSetup`] trait implemented by the
+//! parameter sets of each oscillator, and the generic [`optimizer::Optimizer`] which simulates
+//! and searches the most profitable configurations of any such setup.
+
 //! The `pnl` module provides the core logic for simulating profit and loss.
 //!
 //! This includes the main `simulate` function which calculates the potential
 //! profit or loss based on provided trigger signals and simulation parameters.
 
+//! The `rsi` module contains the logic related to the Relative Strength Index
+//! and its utilization in the PnL simulations, sweeping the RSI length together
+//! with its overbought and oversold levels.
+
+//! The `sampler` module contains the sampled search, simulating randomly or Latin hypercube
+//! sampled configurations of a range until an evaluation or wall-clock budget is spent.
+
+//! The `search` module contains the [`search::SearchSpace`] trait, exposing the configurations of a setup
+//! as integer parameters to the optimizers sampling a range instead of expanding it.
+
 //! The `stochastic` module contains the logic related to stochastic calculations
 //! and their utilization in the PnL simulations.
 //!
 //! This encompasses the generation of stochastic values, the calculation of profit
 //! and loss based on these values, and any related utility functions and structures.
 
+//! The `tpe` module contains the Tree-structured Parzen Estimator, proposing each configuration to simulate
+//! from the scores of the previous ones, for objectives too expensive to sample blindly.
+
+//! The `walk_forward` module contains the walk-forward optimization, ranking configurations on rolling
+//! in-sample windows and trading each winner on the out-of-sample klines following its window.
+
+pub mod amount;
+pub mod genetic;
+pub mod holdout;
+pub mod macd;
 pub mod models;
+pub mod objective;
+pub mod optimizer;
 pub mod pnl;
-pub mod stochastic;
\ No newline at end of file
+pub mod rsi;
+pub mod sampler;
+pub mod search;
+pub mod stochastic;
+pub mod tpe;
+pub mod walk_forward;
\ No newline at end of file
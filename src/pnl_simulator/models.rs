@@ -5,9 +5,11 @@
 /// - `net_profit`: Total returns of the strategy subtracting losses and commissions.
 /// - `gross_profit`: The sum of all profitable trades.
 /// - `gross_loss`: The sum of all losing trades.
-/// - `buy_and_hold_return`: The return if we just bought and held the asset without trading.
-/// - `profit_factor`: The ratio of gross profit to gross loss. A value greater than 1 indicates a profitable system.
+/// - `buy_and_hold_return`: The return if we just bought and held the asset without trading, paying the same fees, slippage and spread.
+///   Unleveraged when trading futures.
+/// - `profit_factor`: The ratio of gross profit to gross loss. A value greater than 1 indicates a profitable system, 0 without losing trades.
 /// - `commission_paid`: Total commission paid for all trades.
+/// - `funding_paid`: Total funding paid by the futures positions, negative if more was received than paid.
 /// - `total_closed_trades`: Total number of closed trades.
 /// - `num_winning_trades`: Number of trades that resulted in profit.
 /// - `num_losing_trades`: Number of trades that resulted in loss.
@@ -19,8 +21,16 @@
 /// - `largest_losing_trade`: The largest loss from a single trade.
 /// - `avg_ticks_in_winning_trades`: Average number of ticks (time periods) that winning trades were held.
 /// - `avg_ticks_in_losing_trades`: Average number of ticks (time periods) that losing trades were held.
+/// - `max_drawdown`: The largest decline of the mark-to-market equity from a preceding peak, in funds.
+/// - `max_drawdown_percent`: The largest decline of the equity from a preceding peak, as a percentage of that peak.
+/// - `max_drawdown_duration`: The longest number of ticks the equity stayed below a preceding peak.
+/// - `exposure`: Percentage of ticks a position was held in, also known as time in market.
+/// - `sharpe_ratio`: Annualized mean of the per-tick equity returns divided by their standard deviation.
+/// - `sortino_ratio`: Annualized mean of the per-tick equity returns divided by their downside deviation.
+/// - `calmar_ratio`: `cagr` divided by `max_drawdown_percent`.
+/// - `cagr`: Compound annual growth rate of the equity, in percent. Infinite if a gain annualized over a short sample overflows.
 ///
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct PnL {
     pub net_profit: f64,
     pub gross_profit: f64,
@@ -28,6 +38,7 @@ pub struct PnL {
     pub buy_and_hold_return: f64,
     pub profit_factor: f64,
     pub commission_paid: Option<f64>,
+    pub funding_paid: f64,
     pub total_closed_trades: i32,
     pub num_winning_trades: i32,
     pub num_losing_trades: i32,
@@ -39,6 +50,14 @@ pub struct PnL {
     pub largest_losing_trade: f64,
     pub avg_ticks_in_winning_trades: f64,
     pub avg_ticks_in_losing_trades: f64,
+    pub max_drawdown: f64,
+    pub max_drawdown_percent: f64,
+    pub max_drawdown_duration: usize,
+    pub exposure: f64,
+    pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+    pub calmar_ratio: f64,
+    pub cagr: f64,
 }
 /// `TriggerSignal` struct holds data used for calculating PnL analysis
 ///
@@ -48,9 +67,12 @@ pub struct PnL {
 /// - `time_open`   : The time that the kline/candlestick open, represented as a Unix timestamp.
 /// - `time_close`  : The time that the kline/candlestick closed, represented as a Unix timestamp.
 /// - `price_open`  : The price at the opening of the kline/candlestick.
+/// - `price_high`  : The highest price reached during the kline/candlestick.
+/// - `price_low`   : The lowest price reached during the kline/candlestick.
 /// - `price_close` : The price at the closing of the kline/candlestick.
+/// - `volume`      : The quantity of the asset traded during the kline/candlestick.
 ///
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TriggerSignal {
     pub signal_in   : f64,
     pub signal_out  : f64,
@@ -59,5 +81,83 @@ pub struct TriggerSignal {
     pub time_close  : u64,
 
     pub price_open  : f64,
+    pub price_high  : f64,
+    pub price_low   : f64,
     pub price_close : f64,
-}
\ No newline at end of file
+    pub volume      : f64,
+}
+/// The side of a simulated position.
+///
+/// - `Long`    : Bought at entry and sold at exit, profits from rising prices.
+/// - `Short`   : Sold at entry and bought back at exit, profits from falling prices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Long,
+    Short,
+}
+
+/// Why a simulated position was closed.
+///
+/// - `Signal`      : The signals crossed back, or reversed into the opposite side.
+/// - `StopLoss`    : The fixed stop-loss was hit.
+/// - `TakeProfit`  : The take-profit was hit.
+/// - `AtrStop`     : The ATR-multiple stop was hit.
+/// - `TrailingStop`: The trailing stop was hit.
+/// - `Liquidation` : The futures position was liquidated, its margin falling to the maintenance margin.
+/// - `EndOfData`   : The position was still open at the last tick.
+/// - `WindowEnd`   : The position was still open at the last tick of a walk-forward window, see
+///   [`crate::pnl_simulator::walk_forward::WalkForwardResult`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    Signal,
+    StopLoss,
+    TakeProfit,
+    AtrStop,
+    TrailingStop,
+    Liquidation,
+    EndOfData,
+    WindowEnd,
+}
+
+/// A single closed trade of a simulation.
+///
+/// # Fields
+/// - `direction`   : The side of the position.
+/// - `entry_time`  : The time of the entry fill: the `time_close` of the tick for fills at its close or VWAP,
+///   see [`crate::pnl_simulator::pnl::Execution`], its `time_open` otherwise.
+/// - `exit_time`   : The time of the exit fill, like `entry_time`.
+/// - `entry_price` : The fill price of the entry.
+/// - `exit_price`  : The fill price of the exit.
+/// - `quantity`    : The quantity of the asset bought, or sold short, at entry.
+/// - `fees`        : The fees of the entry and the exit, in funds.
+/// - `funding`     : The funding paid while the futures position was held, negative if received.
+/// - `profit`      : The profit of the trade after fees and funding, negative for a loss.
+/// - `ticks_held`  : The number of ticks between entry and exit.
+/// - `exit_reason` : Why the position was closed, see [ExitReason].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub direction   : Direction,
+    pub entry_time  : u64,
+    pub exit_time   : u64,
+    pub entry_price : f64,
+    pub exit_price  : f64,
+    pub quantity    : f64,
+    pub fees        : f64,
+    pub funding     : f64,
+    pub profit      : f64,
+    pub ticks_held  : usize,
+    pub exit_reason : ExitReason,
+}
+
+/// The outcome of a simulation including its trade log, see [`crate::pnl_simulator::pnl::backtest`].
+///
+/// # Fields
+/// - `pnl`     : The aggregated performance metrics, as returned by [`crate::pnl_simulator::pnl::simulate`].
+/// - `trades`  : Every closed trade, in the order they were closed.
+/// - `equity`  : The mark-to-market equity at the close of every tick of the signals.
+#[derive(Debug, Default)]
+pub struct Backtest {
+    pub pnl     : PnL,
+    pub trades  : Vec<Trade>,
+    pub equity  : Vec<f64>,
+}
@@ -0,0 +1,142 @@
+use std::{fmt, sync::Arc};
+
+use super::models::PnL;
+
+/// The measure configurations are ranked by in [`crate::pnl_simulator::optimizer::Optimizer::top_net_profit`].
+///
+/// # Variants
+/// - `NetProfit`: [`PnL::net_profit`], the default.
+/// - `ProfitFactor`: [`PnL::profit_factor`]. Configurations with winning trades but no losing ones, whose profit factor is 0,
+///   score `f64::MAX` instead and rank above any other.
+/// - `PercentProfitable`: [`PnL::percent_profitable`].
+/// - `NetProfitPerTrade`: [`PnL::net_profit`] divided by [`PnL::total_closed_trades`], 0 without closed trades.
+/// - `RiskAdjustedReturn`: [`PnL::net_profit`] divided by the absolute [`PnL::largest_losing_trade`],
+///   the net profit itself if no trade lost.
+/// - `SharpeRatio`: [`PnL::sharpe_ratio`].
+/// - `SortinoRatio`: [`PnL::sortino_ratio`].
+/// - `CalmarRatio`: [`PnL::calmar_ratio`].
+/// - `Cagr`: [`PnL::cagr`].
+/// - `Custom`: Any closure over [`PnL`], greater values rank higher.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::{models::PnL, objective::Objective};
+/// use std::sync::Arc;
+///
+/// let pnl = PnL { net_profit: 120.0, total_closed_trades: 40, largest_losing_trade: -30.0, ..Default::default() };
+///
+/// assert_eq!(Objective::NetProfit.score(&pnl), 120.0);
+/// assert_eq!(Objective::NetProfitPerTrade.score(&pnl), 3.0);
+/// assert_eq!(Objective::RiskAdjustedReturn.score(&pnl), 4.0);
+///
+/// let ratios = PnL { sharpe_ratio: 1.8, cagr: 35.0, ..Default::default() };
+/// assert_eq!(Objective::SharpeRatio.score(&ratios), 1.8);
+/// assert_eq!(Objective::Cagr.score(&ratios), 35.0);
+///
+/// // All winners outrank a mix of winners and losers
+/// let all_winners = PnL { gross_profit: 50.0, gross_loss: 0.0, profit_factor: 0.0, ..Default::default() };
+/// let mixed = PnL { gross_profit: 300.0, gross_loss: -100.0, profit_factor: 3.0, ..Default::default() };
+/// assert!(Objective::ProfitFactor.score(&all_winners) > Objective::ProfitFactor.score(&mixed));
+///
+/// let custom = Objective::Custom(Arc::new(|pnl: &PnL| pnl.net_profit - pnl.total_closed_trades as f64));
+/// assert_eq!(custom.score(&pnl), 80.0);
+/// ```
+#[derive(Clone, Default)]
+pub enum Objective {
+    #[default]
+    NetProfit,
+    ProfitFactor,
+    PercentProfitable,
+    NetProfitPerTrade,
+    RiskAdjustedReturn,
+    SharpeRatio,
+    SortinoRatio,
+    CalmarRatio,
+    Cagr,
+    Custom(Arc<dyn Fn(&PnL) -> f64 + Send + Sync>),
+}
+
+impl Objective {
+    /// Scores `pnl`, greater values rank higher.
+    pub fn score(&self, pnl: &PnL) -> f64 {
+        match self {
+            Objective::NetProfit            => pnl.net_profit,
+            Objective::ProfitFactor         => profit_factor(pnl),
+            Objective::PercentProfitable    => pnl.percent_profitable,
+            Objective::NetProfitPerTrade    => {
+                if pnl.total_closed_trades > 0 { pnl.net_profit / pnl.total_closed_trades as f64 } else { 0.0 }
+            },
+            Objective::RiskAdjustedReturn   => {
+                if pnl.largest_losing_trade < 0.0 { pnl.net_profit / pnl.largest_losing_trade.abs() } else { pnl.net_profit }
+            },
+            Objective::SharpeRatio          => pnl.sharpe_ratio,
+            Objective::SortinoRatio         => pnl.sortino_ratio,
+            Objective::CalmarRatio          => pnl.calmar_ratio,
+            Objective::Cagr                 => pnl.cagr,
+            Objective::Custom(score)        => score(pnl),
+        }
+    }
+}
+
+impl fmt::Debug for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Objective::NetProfit            => write!(f, "NetProfit"),
+            Objective::ProfitFactor         => write!(f, "ProfitFactor"),
+            Objective::PercentProfitable    => write!(f, "PercentProfitable"),
+            Objective::NetProfitPerTrade    => write!(f, "NetProfitPerTrade"),
+            Objective::RiskAdjustedReturn   => write!(f, "RiskAdjustedReturn"),
+            Objective::SharpeRatio          => write!(f, "SharpeRatio"),
+            Objective::SortinoRatio         => write!(f, "SortinoRatio"),
+            Objective::CalmarRatio          => write!(f, "CalmarRatio"),
+            Objective::Cagr                 => write!(f, "Cagr"),
+            Objective::Custom(_)            => write!(f, "Custom"),
+        }
+    }
+}
+
+/// Requirements a configuration has to meet to be ranked at all.
+///
+/// # Fields
+/// - `min_closed_trades`: Default (None). Minimum [`PnL::total_closed_trades`].
+/// - `min_profit_factor`: Default (None). Minimum [`PnL::profit_factor`], always met without losing trades if some trade won.
+/// - `min_percent_profitable`: Default (None). Minimum [`PnL::percent_profitable`].
+/// - `max_drawdown_percent`: Default (None). Maximum [`PnL::max_drawdown_percent`].
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::{models::PnL, objective::Constraints};
+///
+/// let constraints = Constraints { min_closed_trades: Some(30), ..Default::default() };
+///
+/// assert!(!constraints.is_satisfied(&PnL { net_profit: 500.0, total_closed_trades: 2, ..Default::default() }));
+/// assert!(constraints.is_satisfied(&PnL { net_profit: 300.0, total_closed_trades: 90, ..Default::default() }));
+///
+/// let constraints = Constraints { max_drawdown_percent: Some(25.0), ..Default::default() };
+/// assert!(!constraints.is_satisfied(&PnL { net_profit: 900.0, max_drawdown_percent: 60.0, ..Default::default() }));
+///
+/// let constraints = Constraints { min_profit_factor: Some(1.5), ..Default::default() };
+/// assert!(constraints.is_satisfied(&PnL { gross_profit: 50.0, gross_loss: 0.0, profit_factor: 0.0, ..Default::default() }));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Constraints {
+    pub min_closed_trades       : Option<i32>,
+    pub min_profit_factor       : Option<f64>,
+    pub min_percent_profitable  : Option<f64>,
+    pub max_drawdown_percent    : Option<f64>,
+}
+
+impl Constraints {
+    /// Returns whether `pnl` meets every configured requirement.
+    pub fn is_satisfied(&self, pnl: &PnL) -> bool {
+        self.min_closed_trades.map_or(true, |min| pnl.total_closed_trades >= min)
+            && self.min_profit_factor.map_or(true, |min| profit_factor(pnl) >= min)
+            && self.min_percent_profitable.map_or(true, |min| pnl.percent_profitable >= min)
+            && self.max_drawdown_percent.map_or(true, |max| pnl.max_drawdown_percent <= max)
+    }
+}
+
+/// Returns the [`PnL::profit_factor`], or `f64::MAX` if some trade won and none lost, leaving it undefined.
+fn profit_factor(pnl: &PnL) -> f64 {
+    if pnl.gross_loss == 0.0 && pnl.gross_profit > 0.0 { f64::MAX } else { pnl.profit_factor }
+}
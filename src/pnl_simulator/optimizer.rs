@@ -0,0 +1,664 @@
+use std::{
+    cmp::Ordering,
+    cmp::Reverse,
+    collections::BinaryHeap,
+    error::Error,
+    fmt::Debug,
+    marker::PhantomData,
+    ops::Range,
+    sync::Arc,
+};
+
+use crate::exchange::chart_data::klines::{binance, coinbase, Intervals, KlineParams, KlinesSubset};
+use crate::oscillators::models::Hlc;
+use super::{
+    models::{Backtest, PnL, TriggerSignal},
+    objective::{Constraints, Objective},
+    pnl::{backtest, simulate, simulate_fast, AtrStop, EntryOrder, Execution, FeeCurrency, FeeTier, Futures, PositionMode, SimulateParams, Sizing, Slippage}
+};
+
+use rayon::prelude::*;
+
+/// A parameter set of a technical oscillator which can be simulated and optimized by [`Optimizer`].
+///
+/// Implementors are the configuration structs of each oscillator, e.g. [`crate::pnl_simulator::stochastic::PnlParams`],
+/// [`crate::pnl_simulator::rsi::RsiParams`] or [`crate::pnl_simulator::macd::MacdParams`].
+/// Given the price data, a setup yields two signal lines: the simulator enters while `signal_in` is above `signal_out`,
+/// and exits while it is below. See [`TriggerSignal`].
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::oscillators::models::Hlc;
+/// use oscillatorsetups::pnl_simulator::optimizer::OscillatorSetup;
+/// use std::ops::RangeInclusive;
+///
+/// /// Close price crossing above its own lagged value
+/// #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// struct Momentum { lag: u16 }
+///
+/// impl OscillatorSetup for Momentum {
+///     type Range = RangeInclusive<u16>;
+///
+///     fn configurations(range: &Self::Range) -> Vec<Self> {
+///         range.clone().map(|lag| Momentum { lag }).collect()
+///     }
+///
+///     fn signal_lines(&self, price_data: &[Hlc]) -> (Vec<Option<f64>>, Vec<Option<f64>>) {
+///         let lag = self.lag as usize;
+///         let close = price_data.iter().map(|hlc| Some(hlc.price_close)).collect();
+///         let lagged = (0..price_data.len())
+///             .map(|ix| ix.checked_sub(lag).map(|prev| price_data[prev].price_close))
+///             .collect();
+///         (close, lagged)
+///     }
+/// }
+///
+/// assert_eq!(Momentum::configurations(&(2..=4)).len(), 3);
+/// ```
+pub trait OscillatorSetup: Debug + Clone + Ord + Send + Sync + 'static {
+    /// The ranges of parameters the setup is swept over, e.g. [`crate::pnl_simulator::stochastic::PnlRange`].
+    type Range;
+
+    /// Expands `range` into every valid configuration to be simulated.
+    fn configurations(range: &Self::Range) -> Vec<Self>;
+
+    /// Calculates the `(signal_in, signal_out)` lines for every tick of `price_data`.
+    /// Ticks where either line is `None` are left out of the simulation.
+    fn signal_lines(&self, price_data: &[Hlc]) -> (Vec<Option<f64>>, Vec<Option<f64>>);
+
+    /// Yields every configuration of `range` together with its `(signal_in, signal_out)` lines, as a parallel iterator.
+    ///
+    /// The default implementation calculates [`OscillatorSetup::signal_lines`] for each configuration independently.
+    /// Setups whose lines share intermediate results across configurations should override it to calculate those once,
+    /// see [`crate::pnl_simulator::stochastic::PnlParams`].
+    fn sweep<'r>(range: &'r Self::Range, price_data: &'r [Hlc]) -> impl ParallelIterator<Item = (Self, SignalLine, SignalLine)> + 'r {
+        Self::configurations(range).into_par_iter().map(move |setup| {
+            let (signal_in, signal_out) = setup.signal_lines(price_data);
+            (setup, Arc::new(signal_in), Arc::new(signal_out))
+        })
+    }
+}
+
+/// A signal line shared between the configurations of a sweep, see [`OscillatorSetup::sweep`].
+pub type SignalLine = Arc<Vec<Option<f64>>>;
+
+/// Simulates and optimizes an oscillator setup `S` for a given financial exchange.
+///
+/// The oscillator specific types are aliases of this struct, e.g. [`crate::pnl_simulator::stochastic::Stochastic`],
+/// [`crate::pnl_simulator::rsi::Rsi`] and [`crate::pnl_simulator::macd::Macd`].
+///
+/// # Parameters
+/// * `exchange`: The name of the exchange to pull data from, e.g., "coinbase" or "binance".
+/// * `klines`: Vec<[KlinesSubset]> Subset of K-line data representing certain attributes of the price candle in a time frame.
+/// * `lhc`: Vec<[Hlc]> High, Low, Close (HLC) values derived from the K-line data.
+/// * `interval`: The [`Intervals`] of the K-line data, annualizing the return ratios of [`PnL`].
+/// * `capital`: The starting capital for the simulation.
+/// * `exchange_fee`: The fee charged by the exchange for each transaction, the taker rate.
+/// * `maker_fee`, `fee_tiers`, `fee_currency`: The maker rate, the volume-based [`FeeTier`]s and the [`FeeCurrency`], see [`SimulateParams`].
+/// * `min_qty`: The minimum quantity of an asset that can be bought or sold.
+/// * `min_price`: The minimum price at which an asset can be bought or sold.
+/// * `asset_scale`: The precision with which assets are tracked.
+/// * `funds_scale`: The precision with which funds are tracked.
+/// * `position_mode`: The [`PositionMode`] deciding whether crossovers are traded long, short or both.
+/// * `stop_loss`, `take_profit`, `atr_stop`, `trailing_stop`: Protective exits, see [`SimulateParams`].
+/// * `sizing`: The [`Sizing`] of each entry.
+/// * `slippage`, `spread`: The [`Slippage`] and bid/ask spread applied to every fill.
+/// * `execution`: The [`Execution`] timing of the fills.
+/// * `entry_order`: The [`EntryOrder`] positions are entered with.
+/// * `futures`: The leverage, margin and funding of [`Futures`], if traded instead of spot.
+/// * `pnl_fast` : Screens the configurations of [`Optimizer::top_net_profit`] with the faster but less precise [`simulate_fast`].
+/// * `rescore` : Re-scores the top configurations with the precise [`simulate`] when `pnl_fast` is set.
+/// * `top_n` : The number of top configurations kept by [`Optimizer::top_net_profit`].
+/// * `objective` : The [`Objective`] configurations are ranked by.
+/// * `constraints` : The [`Constraints`] a configuration has to satisfy to be ranked.
+///
+/// ## Reference for implements
+/// * [`Optimizer::new`] - instance with default and derived values
+/// * [`Optimizer::pnl`] - simple one config pnl request
+/// * [`Optimizer::backtest`] - one config pnl request including the trade log
+/// * [`Optimizer::top_net_profit`] - ranks a range of parameters by the objective, keeping the top `top_n` configurations.
+/// * [`Optimizer::score`] - scores a [`PnL`] by the objective and constraints
+/// * [`Optimizer::holdout`] - ranks on training klines, reporting the top configurations on held-out klines as well
+/// * [`Optimizer::genetic`] - searches a range with a genetic algorithm, for ranges too wide to simulate exhaustively
+/// * [`Optimizer::sample`] - simulates sampled configurations of a range until an evaluation or time budget is spent
+/// * [`Optimizer::tpe`] - searches a range with a Tree-structured Parzen Estimator, for expensive simulations
+#[derive(Debug)]
+pub struct Optimizer<'a, S> {
+    pub exchange    : &'a str,
+    pub klines  : Vec<KlinesSubset>,
+    pub lhc     : Vec<Hlc>,
+    pub interval: Intervals,
+
+    pub capital     : f64,
+    pub exchange_fee: Option<f64>,
+    pub maker_fee   : Option<f64>,
+    pub fee_tiers   : Vec<FeeTier>,
+    pub fee_currency: FeeCurrency,
+    pub min_qty     : Option<f64>,
+    pub min_price   : Option<f64>,
+    pub asset_scale : u32,
+    pub funds_scale : u32,
+    pub position_mode   : PositionMode,
+    pub stop_loss       : Option<f64>,
+    pub take_profit     : Option<f64>,
+    pub atr_stop        : Option<AtrStop>,
+    pub trailing_stop   : Option<f64>,
+    pub sizing          : Sizing,
+    pub slippage        : Slippage,
+    pub spread          : Option<f64>,
+    pub execution       : Execution,
+    pub entry_order     : EntryOrder,
+    pub futures         : Option<Futures>,
+
+    pub pnl_fast    : bool,
+    pub rescore     : bool,
+    pub top_n       : usize,
+    pub objective   : Objective,
+    pub constraints : Constraints,
+
+    pub setup       : PhantomData<S>,
+}
+
+impl<'a, S: OscillatorSetup> Optimizer<'a, S> {
+    /// Creates a new instance of the `Optimizer` struct using data from the specified exchange.
+    ///
+    /// The method fetches K-line data based on the given exchange and then constructs an [`Optimizer`] instance with default and derived values.
+    ///
+    /// # Default Values
+    /// - `capital`: 1000.0; Use [Optimizer::capital] method to set different amount
+    /// - `exchange_fee`: None; Use [Optimizer::exchange_fee] method to set fee
+    /// - `maker_fee`, `fee_tiers`: None; Use the methods of the same name to charge resting orders and high volumes less
+    /// - `fee_currency`: [FeeCurrency::Native]; Use [Optimizer::fee_currency] to pay the fees in quote or a discounted third asset
+    /// - `min_qty`: None; Use [Optimizer::min_qty] to update amount
+    /// - `min_price`: None; Use [Optimizer::min_price] to update amount
+    /// - `asset_scale`: 8; Use [Optimizer::asset_scale] to change
+    /// - `funds_scale`: 8; Use [Optimizer::funds_scale] to change
+    /// - `position_mode`: [PositionMode::LongOnly]; Use [Optimizer::position_mode] to trade the short side as well
+    /// - `stop_loss`, `take_profit`, `atr_stop`, `trailing_stop`: None; Use the methods of the same name to add protective exits
+    /// - `sizing`: [Sizing::AllFunds]; Use [Optimizer::sizing] to stake less than all funds per entry
+    /// - `slippage`: [Slippage::Zero]; Use [Optimizer::slippage] to fill against the trade
+    /// - `spread`: None; Use [Optimizer::spread] to fill buys at the ask and sells at the bid
+    /// - `execution`: [Execution::NextOpen]; Use [Optimizer::execution] to fill at a close or the VWAP instead
+    /// - `entry_order`: [EntryOrder::Market]; Use [Optimizer::entry_order] to enter with resting limit orders
+    /// - `futures`: None; Use [Optimizer::futures] to trade leveraged perpetual futures
+    /// - `pnl_fast`: false; Use [Optimizer::pnl_fast] to screen with `f64` amounts
+    /// - `rescore`: true; Use [Optimizer::rescore] to keep the `f64` scores of a fast screening
+    /// - `top_n`: 100; Use [Optimizer::top_n] to keep more or fewer top configurations
+    /// - `objective`: [Objective::NetProfit]; Use [Optimizer::objective] to rank by another measure
+    /// - `constraints`: none; Use [Optimizer::constraints] to require e.g. a minimum number of closed trades
+    ///
+    /// # Parameters
+    /// - `exchange`: A string slice that represents the name of the exchange. Only "coinbase" and "binance" are currently supported.
+    /// - `params`: An instance of [`KlineParams`] which provides parameters for fetching K-line data.
+    ///
+    /// # Returns
+    /// - A `Result` which is `Ok` if a new [`Optimizer`] instance is successfully created. Returns an error if an invalid exchange name is provided or if there's an issue fetching the K-line data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oscillatorsetups::exchange::chart_data::klines::{Intervals, KlineParams};
+    /// use oscillatorsetups::pnl_simulator::stochastic::Stochastic;
+    ///
+    /// let kline_params = KlineParams {
+    ///     base_asset: "ETH",
+    ///     quote_asset: "USD",
+    ///     interval: Intervals::H4,
+    ///     limit: 1000,
+    ///     base_url:None,
+    ///     source: Some("api"),
+    /// };
+    ///
+    /// let stochastic_instance = Stochastic::new("coinbase", kline_params);
+    /// match stochastic_instance {
+    ///     Ok(stochastic) => println!("{:?}", stochastic),
+    ///     Err(e) => eprintln!("Failed to create Stochastic instance: {}", e),
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns an error if a non-supported exchange name is given.
+    /// - May return other errors if there's an issue fetching the K-line data
+    pub fn new(exchange: &'a str, params: KlineParams,) -> Result<Self, Box<dyn Error>> {
+
+        let interval = params.interval;
+        let klines = match exchange {
+            "coinbase"  => coinbase(params)?,
+            "binance"   => binance(params)?,
+            _ => return Err(Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid exchange"))),
+        };
+
+        Ok(Self::from_klines(exchange, klines, interval))
+    }
+
+    /// Creates a new instance of the `Optimizer` struct over the given klines, e.g. loaded from a file, with the default values of [`Optimizer::new`].
+    ///
+    /// # Parameters
+    /// - `exchange`: The name of the exchange the klines were fetched from.
+    /// - `klines`: The [`KlinesSubset`]s to simulate on, ordered by time.
+    /// - `interval`: The interval of the klines.
+    ///
+    /// # Examples
+    /// ```
+    /// use oscillatorsetups::exchange::chart_data::klines::{Intervals, KlinesSubset};
+    /// use oscillatorsetups::pnl_simulator::stochastic::{PnlParams, Stochastic};
+    ///
+    /// let klines: Vec<KlinesSubset> = (0..50u64).map(|ix| {
+    ///     let price = 100.0 + (ix % 10) as f64;
+    ///     KlinesSubset { time_open: ix * 60_000, price_open: price, price_low: price - 1.0, price_high: price + 1.0, price_close: price, time_close: ix * 60_000 + 59_999, volume: 1.0 }
+    /// }).collect();
+    /// let stochastic = Stochastic::from_klines("binance", klines, Intervals::M1);
+    ///
+    /// assert_eq!(stochastic.lhc.len(), 50);
+    /// assert_eq!(stochastic.capital, 1000.0);
+    /// assert!(stochastic.pnl(PnlParams { k_length: 5, k_smoothing: 3, d_length: 3 }).total_closed_trades > 0);
+    /// ```
+    pub fn from_klines(exchange: &'a str, klines: Vec<KlinesSubset>, interval: Intervals) -> Self {
+        let lhc: Vec<Hlc> = klines
+            .iter()
+            .map(|kline| Hlc {
+                price_high: kline.price_high,
+                price_low: kline.price_low,
+                price_close: kline.price_close,
+            })
+            .collect();
+
+        Self { exchange, klines, lhc, interval,
+            capital         : 1000.0,
+            exchange_fee    : None,
+            maker_fee       : None,
+            fee_tiers       : vec![],
+            fee_currency    : FeeCurrency::Native,
+
+            min_qty         : None,
+            min_price       : None,
+
+            asset_scale     : 8,
+            funds_scale     : 8,
+            position_mode   : PositionMode::LongOnly,
+            stop_loss       : None,
+            take_profit     : None,
+            atr_stop        : None,
+            trailing_stop   : None,
+            sizing          : Sizing::AllFunds,
+            slippage        : Slippage::Zero,
+            spread          : None,
+            execution       : Execution::NextOpen,
+            entry_order     : EntryOrder::Market,
+            futures         : None,
+
+            pnl_fast        : false,
+            rescore         : true,
+            top_n           : 100,
+            objective       : Objective::NetProfit,
+            constraints     : Constraints::default(),
+
+            setup           : PhantomData,
+        }
+    }
+
+    pub fn capital(mut self, capital: f64) -> Self { self.capital = capital;self }
+
+    pub fn exchange_fee(mut self, exchange_fee: f64) -> Self { self.exchange_fee = Some(exchange_fee);self }
+
+    pub fn maker_fee(mut self, maker_fee: f64) -> Self { self.maker_fee = Some(maker_fee);self }
+
+    pub fn fee_tiers(mut self, fee_tiers: Vec<FeeTier>) -> Self { self.fee_tiers = fee_tiers;self }
+
+    pub fn fee_currency(mut self, fee_currency: FeeCurrency) -> Self { self.fee_currency = fee_currency;self }
+
+    pub fn min_qty(mut self, min_qty: f64) -> Self {self.min_qty = Some(min_qty); self }
+
+    pub fn min_price(mut self, min_price: f64) -> Self {self.min_price = Some(min_price); self }
+
+    pub fn asset_scale(mut self, asset_scale: u32) -> Self {self.asset_scale = asset_scale; self }
+
+    pub fn funds_scale(mut self, funds_scale: u32) -> Self {self.funds_scale = funds_scale; self }
+
+    pub fn position_mode(mut self, position_mode: PositionMode) -> Self {self.position_mode = position_mode; self }
+
+    pub fn stop_loss(mut self, stop_loss: f64) -> Self {self.stop_loss = Some(stop_loss); self }
+
+    pub fn take_profit(mut self, take_profit: f64) -> Self {self.take_profit = Some(take_profit); self }
+
+    pub fn atr_stop(mut self, atr_stop: AtrStop) -> Self {self.atr_stop = Some(atr_stop); self }
+
+    pub fn trailing_stop(mut self, trailing_stop: f64) -> Self {self.trailing_stop = Some(trailing_stop); self }
+
+    pub fn sizing(mut self, sizing: Sizing) -> Self {self.sizing = sizing; self }
+
+    pub fn slippage(mut self, slippage: Slippage) -> Self {self.slippage = slippage; self }
+
+    pub fn spread(mut self, spread: f64) -> Self {self.spread = Some(spread); self }
+
+    pub fn execution(mut self, execution: Execution) -> Self {self.execution = execution; self }
+
+    pub fn entry_order(mut self, entry_order: EntryOrder) -> Self {self.entry_order = entry_order; self }
+
+    pub fn futures(mut self, futures: Futures) -> Self {self.futures = Some(futures); self }
+
+    pub fn pnl_fast(mut self, pnl_fast: bool) -> Self {self.pnl_fast = pnl_fast; self }
+
+    pub fn rescore(mut self, rescore: bool) -> Self {self.rescore = rescore; self }
+
+    pub fn top_n(mut self, top_n: usize) -> Self {self.top_n = top_n; self }
+
+    pub fn objective(mut self, objective: Objective) -> Self {self.objective = objective; self }
+
+    pub fn constraints(mut self, constraints: Constraints) -> Self {self.constraints = constraints; self }
+
+    /// Scores `pnl` by the `objective`.
+    ///
+    /// # Returns
+    /// - `None` if `pnl` does not satisfy the `constraints`, or the score is not finite,
+    ///   e.g. the infinite [`PnL::cagr`] of a gain annualized over a short sample.
+    pub fn score(&self, pnl: &PnL) -> Option<f64> {
+        if !self.constraints.is_satisfied(pnl) { return None; }
+
+        let score = self.objective.score(pnl);
+        if score.is_finite() { Some(score) } else { None }
+    }
+
+    /// Calculates the Profit and Loss ([PnL]) based on the given oscillator setup.
+    ///
+    /// This method first computes the signal lines of the setup, see [`OscillatorSetup::signal_lines`].
+    /// It then identifies data points where both lines are available.
+    /// These points are then used to generate trigger signals which are subsequently fed into a simulation to determine the [PnL].
+    /// The precise [`simulate`] is always used, regardless of `pnl_fast`.
+    ///
+    /// # Parameters
+    /// - `setup`: The oscillator configuration, e.g. [`crate::pnl_simulator::stochastic::PnlParams`].
+    ///
+    /// # Returns
+    /// - An instance of [`PnL`] representing the result of the simulation based on the derived trigger signals.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oscillatorsetups::exchange::chart_data::klines::{Intervals, KlinesSubset};
+    /// use oscillatorsetups::pnl_simulator::stochastic::{PnlParams, Stochastic};
+    ///
+    /// // Steadily rising by 1 per kline
+    /// let klines: Vec<KlinesSubset> = (0..60u64).map(|ix| {
+    ///     let price = 100.0 + ix as f64;
+    ///     KlinesSubset { time_open: ix * 3_600_000, price_open: price, price_low: price - 0.5, price_high: price + 0.5, price_close: price, time_close: ix * 3_600_000 + 3_599_999, volume: 1.0 }
+    /// }).collect();
+    /// let stochastic = Stochastic::from_klines("binance", klines, Intervals::H1);
+    ///
+    /// let result = stochastic.pnl(PnlParams { k_length:14, k_smoothing:3, d_length:3, });
+    /// // The stochastic is pinned at its top, %K never crosses %D, while buying and holding gains
+    /// assert_eq!(result.total_closed_trades, 0);
+    /// assert_eq!(result.buy_and_hold_return, 358.97);
+    /// ```
+    pub fn pnl(&self, setup:S, ) -> PnL {
+        self.pnl_within(setup, 0..self.klines.len())
+    }
+
+    /// Same as [`Optimizer::pnl`], simulating only the klines within `ticks`.
+    /// The signal lines are calculated over all klines, so the ones before `ticks` serve as warm-up.
+    pub(super) fn pnl_within(&self, setup:S, ticks: Range<usize>) -> PnL {
+        let (signal_in, signal_out) = setup.signal_lines(&self.lhc);
+
+        simulate(self.simulate_params(self.trigger_signals(&signal_in, &signal_out, ticks)))
+    }
+
+    /// Same as [`Optimizer::pnl`], additionally returning every closed trade of the simulation, see [`backtest`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oscillatorsetups::exchange::chart_data::klines::{Intervals, KlinesSubset};
+    /// use oscillatorsetups::pnl_simulator::stochastic::{PnlParams, Stochastic};
+    ///
+    /// // The price cycles every 12 klines
+    /// let klines: Vec<KlinesSubset> = (0..120u64).map(|ix| {
+    ///     let price = 100.0 + 10.0 * (ix as f64 * std::f64::consts::TAU / 12.0).sin();
+    ///     KlinesSubset { time_open: ix * 3_600_000, price_open: price, price_low: price - 1.0, price_high: price + 1.0, price_close: price, time_close: ix * 3_600_000 + 3_599_999, volume: 1.0 }
+    /// }).collect();
+    /// let stochastic = Stochastic::from_klines("binance", klines, Intervals::H1);
+    ///
+    /// let setup = PnlParams { k_length:5, k_smoothing:3, d_length:3, };
+    /// let result = stochastic.backtest(setup.clone());
+    ///
+    /// // The trades add up to the aggregate of Optimizer::pnl
+    /// let pnl = stochastic.pnl(setup);
+    /// assert_eq!(result.trades.len() as i32, pnl.total_closed_trades);
+    /// assert!((result.trades.iter().map(|trade| trade.profit).sum::<f64>() - pnl.net_profit).abs() < 1e-6);
+    /// ```
+    pub fn backtest(&self, setup:S, ) -> Backtest {
+        let (signal_in, signal_out) = setup.signal_lines(&self.lhc);
+
+        backtest(self.simulate_params(self.trigger_signals(&signal_in, &signal_out, 0..self.klines.len())))
+    }
+
+    /// Maps the klines within `ticks` where both signal lines are available to trigger signals.
+    pub(super) fn trigger_signals(&self, signal_in: &[Option<f64>], signal_out: &[Option<f64>], ticks: Range<usize>) -> Vec<TriggerSignal> {
+        signal_in[ticks.clone()]
+            .iter()
+            .zip(&signal_out[ticks.clone()])
+            .zip(ticks)
+            .filter_map(|((line_in, line_out), indx)| Some(TriggerSignal {
+                signal_in   : (*line_in)?,
+                signal_out  : (*line_out)?,
+                time_open   : self.klines[indx].time_open,
+                price_open  : self.klines[indx].price_open,
+                price_high  : self.klines[indx].price_high,
+                price_low   : self.klines[indx].price_low,
+                time_close  : self.klines[indx].time_close,
+                price_close : self.klines[indx].price_close,
+                volume      : self.klines[indx].volume,
+            })).collect()
+    }
+
+    /// Wraps the trigger `signals` into [`SimulateParams`] carrying the settings of this instance.
+    pub(super) fn simulate_params(&self, signals: Vec<TriggerSignal>) -> SimulateParams {
+        SimulateParams::new(signals)
+            .capital(self.capital)
+            .exchange_fee(self.exchange_fee)
+            .maker_fee(self.maker_fee)
+            .fee_tiers(self.fee_tiers.clone())
+            .fee_currency(self.fee_currency)
+            .min_qty(self.min_qty)
+            .min_price(self.min_price)
+            .asset_scale(self.asset_scale)
+            .funds_scale(self.funds_scale)
+            .position_mode(self.position_mode)
+            .stop_loss(self.stop_loss)
+            .take_profit(self.take_profit)
+            .atr_stop(self.atr_stop)
+            .trailing_stop(self.trailing_stop)
+            .sizing(self.sizing)
+            .slippage(self.slippage)
+            .spread(self.spread)
+            .execution(self.execution)
+            .entry_order(self.entry_order)
+            .futures(self.futures.clone())
+            .interval(self.interval)
+    }
+
+    /// Identifies the top configurations (parameters) resulting in the highest scores using the given range.
+    ///
+    /// This method systematically explores every configuration produced by [`OscillatorSetup::sweep`].
+    /// It then calculates the Profit and Loss (PnL) for each configuration and keeps track of the top `top_n` results
+    /// by the `objective`, net profit unless changed with [`Optimizer::objective`].
+    /// Configurations not satisfying the `constraints` are left out, see [`Optimizer::score`].
+    ///
+    /// With `pnl_fast` set, every configuration is screened with [`simulate_fast`]. Unless disabled with [`Optimizer::rescore`],
+    /// the retained top configurations are then simulated again with the precise [`simulate`] and ranked by those results.
+    ///
+    /// The method leverages parallel processing to speed up the computation of PnL across different configurations.
+    /// Each worker thread keeps its own bounded heap of top results, the heaps are merged once the sweep is done.
+    ///
+    /// # Returns
+    /// - The top configurations with their score, sorted in ascending order so the best configuration is last.
+    ///
+    /// # Parameters
+    /// - `range`: The parameter ranges of the setup, e.g. [`crate::pnl_simulator::stochastic::PnlRange`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oscillatorsetups::exchange::chart_data::klines::{Intervals, KlinesSubset};
+    /// use oscillatorsetups::pnl_simulator::objective::{Constraints, Objective};
+    /// use oscillatorsetups::pnl_simulator::stochastic::{PnlRange, Stochastic};
+    ///
+    /// // A 12 kline cycle with deterministic noise, so slow setups trade rarely
+    /// let klines: Vec<KlinesSubset> = (0..300u64).map(|ix| {
+    ///     let price = 100.0 + 10.0 * (ix as f64 * std::f64::consts::TAU / 12.0).sin() + ((ix * 37) % 11) as f64 - 5.0;
+    ///     KlinesSubset { time_open: ix * 3_600_000, price_open: price, price_low: price - 1.0, price_high: price + 1.0, price_close: price, time_close: ix * 3_600_000 + 3_599_999, volume: 1.0 }
+    /// }).collect();
+    /// let stochastic = Stochastic::from_klines("binance", klines, Intervals::H1)
+    ///     .objective(Objective::ProfitFactor)
+    ///     .constraints(Constraints { min_closed_trades: Some(20), ..Default::default() })
+    ///     .top_n(5);
+    ///
+    /// let range = PnlRange {
+    ///     k_length    : 5..=20,
+    ///     k_smoothing : 3..=5,
+    ///     d_length    : 3..=5,
+    /// };
+    ///
+    /// let top_profits = stochastic.top_net_profit(range);
+    /// assert_eq!(top_profits.len(), 5);
+    /// // Ascending, the best configuration is last
+    /// assert!(top_profits.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    /// for (profit, params) in &top_profits {
+    ///     let pnl = stochastic.pnl(params.clone());
+    ///     assert!(pnl.total_closed_trades >= 20);
+    ///     assert_eq!(profit.0, Objective::ProfitFactor.score(&pnl));
+    /// }
+    /// ```
+    ///
+    /// # Note
+    /// - The method uses a parallelized loop (`par_iter`) to compute the PnL for each configuration, ensuring efficient computation on multi-core systems.
+    /// - No locks are taken during the sweep, results are combined with rayon's `fold` and `reduce`.
+    /// - This method can be computationally intensive, especially for larger ranges. Ensure optimal resource management when using it.
+    pub fn top_net_profit(&self, range:S::Range) -> Vec<(Profit, S)> {
+        self.rank(&range, std::slice::from_ref(&(0..self.klines.len()))).pop().unwrap_or_default()
+    }
+
+    /// Carries out [`Optimizer::top_net_profit`] on each window of klines in `windows` at once,
+    /// calculating the signal lines of every configuration a single time.
+    ///
+    /// # Returns
+    /// - The top configurations of each window with their score, in the order of `windows`.
+    pub(super) fn rank(&self, range: &S::Range, windows: &[Range<usize>]) -> Vec<Vec<(Profit, S)>> {
+        let tops = || windows.iter().map(|_| TopN::new(self.top_n)).collect::<Vec<_>>();
+
+        let top_profits = S::sweep(range, &self.lhc)
+            .fold(tops, |mut tops, (setup, signal_in, signal_out)| {
+                for (top, ticks) in tops.iter_mut().zip(windows) {
+                    if let Some(score) = self.screen(&signal_in, &signal_out, ticks.clone()) { top.push((Profit(score), setup.clone())); }
+                }
+                tops
+            })
+            .reduce(tops, |a, b| a.into_iter().zip(b).map(|(a, b)| a.merge(b)).collect());
+
+        top_profits.into_iter().zip(windows).map(|(top, ticks)| self.rescored(top.into_sorted_vec(), ticks.clone())).collect()
+    }
+
+    /// Scores the signal lines of a configuration on the klines within `ticks`,
+    /// simulated with [`simulate_fast`] if `pnl_fast` is set, [`simulate`] otherwise.
+    pub(super) fn screen(&self, signal_in: &[Option<f64>], signal_out: &[Option<f64>], ticks: Range<usize>) -> Option<f64> {
+        let sim_params = self.simulate_params(self.trigger_signals(signal_in, signal_out, ticks));
+        let pnl = if self.pnl_fast { simulate_fast(sim_params) } else { simulate(sim_params) };
+
+        self.score(&pnl)
+    }
+
+    /// Re-scores the ascending `top_profits` on the klines within `ticks` with the precise [`simulate`],
+    /// if they were screened with `pnl_fast` and `rescore` is set. Returns them unchanged otherwise.
+    pub(super) fn rescored(&self, top_profits: Vec<(Profit, S)>, ticks: Range<usize>) -> Vec<(Profit, S)> {
+        if !(self.pnl_fast && self.rescore) { return top_profits; }
+
+        let mut top_profits: Vec<(Profit, S)> = top_profits
+            .into_par_iter()
+            .filter_map(|(_, setup)| Some((Profit(self.score(&self.pnl_within(setup.clone(), ticks.clone()))?), setup)))
+            .collect();
+        top_profits.sort();
+        top_profits
+    }
+}
+
+/// A bounded collection keeping the `n` greatest items pushed into it.
+///
+/// Backed by a min-heap, so the smallest retained item is evicted in O(log n) once the bound is exceeded.
+struct TopN<T: Ord> {
+    n       : usize,
+    heap    : BinaryHeap<Reverse<T>>,
+}
+
+impl<T: Ord> TopN<T> {
+    fn new(n: usize) -> Self { Self { n, heap: BinaryHeap::with_capacity(n + 1) } }
+
+    /// Adds `item`, evicting the smallest item if more than `n` are held.
+    fn push(&mut self, item: T) {
+        self.heap.push(Reverse(item));
+        if self.heap.len() > self.n { self.heap.pop(); }
+    }
+
+    /// Combines two collections, keeping the `n` greatest items of both.
+    fn merge(mut self, other: Self) -> Self {
+        for Reverse(item) in other.heap { self.push(item); }
+        self
+    }
+
+    /// Returns the retained items in ascending order.
+    fn into_sorted_vec(self) -> Vec<T> {
+        self.heap.into_sorted_vec().into_iter().rev().map(|Reverse(item)| item).collect()
+    }
+}
+
+/// A simple structure representing profit, primarily designed for ordering and comparisons.
+///
+/// The `Profit` struct holds a single [`f64`] value, which represents the profit amount,
+/// or more generally the score of the [`Objective`] a configuration is ranked by.
+/// It provides implementations for equality and ordering to facilitate comparisons
+/// and to be used in sorted collections like [`std::collections::BTreeSet`].
+///
+/// # Derive
+/// - `Debug`: Enables support for formatting using `{:?}`.
+/// - `Clone`: Allows the creation of duplicate instances.
+///
+/// # Trait Implementations
+/// - [`PartialEq::eq`]: Enables equality comparisons.
+/// - [`Eq`]: Indicates that all values of this type are reflexive, symmetric, and transitive.
+/// - [`PartialOrd::partial_cmp`]: Enables partial order comparisons.
+/// - [`Ord::cmp`]: Provides a total ordering over `Profit`.
+///
+/// # Examples
+///
+/// ```rust
+/// use oscillatorsetups::pnl_simulator::optimizer::Profit;
+/// let profit1 = Profit(100.5);
+/// let profit2 = Profit(150.0);
+///
+/// assert!(profit1 < profit2);
+/// assert_ne!(profit1, profit2);
+/// ```
+///
+/// # Caveats
+/// - Although `Profit` contains a floating-point number, the implementations for ordering and
+///   equality do not handle NaN values. Ensure that NaN is not used when working with `Profit`.
+#[derive(Debug, Clone)]
+pub struct Profit(pub f64);
+
+impl PartialEq for Profit {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Profit {}
+
+impl PartialOrd for Profit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Profit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap_or(Ordering::Equal)
+    }
+}
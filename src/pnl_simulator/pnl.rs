@@ -1,70 +1,159 @@
 //! A module for simulating Profit and Loss (PnL) based on trading signals.
-use super::models::{PnL, TriggerSignal};
+use std::collections::VecDeque;
+
+use crate::exchange::chart_data::klines::Intervals;
+use crate::oscillators::{atr::atr_for_ticks, models::Hlc};
+use super::{
+    amount::Amount,
+    models::{Backtest, Direction, ExitReason, PnL, Trade, TriggerSignal},
+};
 
 use rust_decimal::prelude::*;
-use rust_decimal_macros::dec;
 
 /// Parameters required for simulating trading.
 /// # Fields
 /// - `signals`         : Vec<[TriggerSignal]>
 /// - `initial_capital` : Default (1000.00). Starting capital, amount of funds initially available for the simulation.
-/// - `exchange_fee`    : Default (None). Exchange fees, if any, paid for each entry and exit.
+/// - `exchange_fee`    : Default (None). Exchange fees, if any, paid for each entry and exit. The taker rate, charged on market fills and stops.
+/// - `maker_fee`       : Default (None). The maker rate charged on fills of resting orders, e.g. the take-profit. Falls back to `exchange_fee`.
+/// - `fee_tiers`       : Default (empty). A volume-based [FeeTier] schedule, overriding both rates once the traded volume reaches a tier.
+/// - `fee_currency`    : Default ([FeeCurrency::Native]). The currency the fees are paid in.
 /// - `min_qty`         : Default (None). Minimum quantity or step size allowed when placing a trading order for a particular asset. It is also known as the "lot size" or "order step size" or "quantity increments"
 /// - `min_price`       : Default (None). Same as min_qty, only this is the minimum price, or price increment allowed when placing order.
 /// - `asset_scale`     : Default (8). The asset displayed precision in your wallet balance
 /// - `funds_scale`     : Default (8). The quote or as price displayed precision in your wallet balance
+/// - `position_mode`   : Default ([PositionMode::LongOnly]). Which side(s) the crossovers of the signals are traded on.
+/// - `stop_loss`       : Default (None). Closes the position once the price moves against it by this fraction of the entry price, e.g. 0.02 for 2%.
+/// - `take_profit`     : Default (None). Closes the position once the price moves in its favour by this fraction of the entry price.
+/// - `atr_stop`        : Default (None). Closes the position once the price moves against it by a multiple of the ATR, see [AtrStop].
+/// - `trailing_stop`   : Default (None). Closes the position once the price retraces by this fraction from its best price since entry.
+/// - `sizing`          : Default ([Sizing::AllFunds]). How much of the funds each entry commits.
+/// - `slippage`        : Default ([Slippage::Zero]). How far fills deviate from the quoted price against the trade.
+/// - `spread`          : Default (None). The bid/ask spread as a fraction of the price, e.g. 0.0004 for 4 bps. Buys fill half of it above, sells below the price.
+/// - `execution`       : Default ([Execution::NextOpen]). When the orders staged by the signals are filled.
+/// - `entry_order`     : Default ([EntryOrder::Market]). Whether positions are entered with market or resting limit orders.
+/// - `futures`         : Default (None). Trades leveraged perpetual futures instead of spot, see [Futures].
+/// - `interval`        : Default ([Intervals::D1]). The duration of a tick, used to annualize `sharpe_ratio`, `sortino_ratio` and `cagr` of [PnL].
+///
+/// The protective exits are evaluated intrabar against `price_high` and `price_low` of every tick, see [`simulate`].
 ///
 /// ## Reference of methods
 /// - [SimulateParams::new] - use constructor to apply `defaults`
 /// - [SimulateParams::capital] - sets initial_capital [`initial_capital`]: SimulateParams::initial_capital
 /// - [SimulateParams::exchange_fee] - sets `exchange_fee`
+/// - [SimulateParams::maker_fee] - sets `maker_fee`
+/// - [SimulateParams::fee_tiers] - sets `fee_tiers`
+/// - [SimulateParams::fee_currency] - sets `fee_currency`
 /// - [SimulateParams::min_qty] - sets `min_qty`
 /// - [SimulateParams::min_price] - sets `min_price`
 /// - [SimulateParams::asset_scale] - sets `asset_scale`
+/// - [SimulateParams::position_mode] - sets `position_mode`
+/// - [SimulateParams::stop_loss] - sets `stop_loss`
+/// - [SimulateParams::take_profit] - sets `take_profit`
+/// - [SimulateParams::atr_stop] - sets `atr_stop`
+/// - [SimulateParams::trailing_stop] - sets `trailing_stop`
+/// - [SimulateParams::sizing] - sets `sizing`
+/// - [SimulateParams::slippage] - sets `slippage`
+/// - [SimulateParams::spread] - sets `spread`
+/// - [SimulateParams::execution] - sets `execution`
+/// - [SimulateParams::entry_order] - sets `entry_order`
+/// - [SimulateParams::futures] - sets `futures`
+/// - [SimulateParams::interval] - sets `interval`
 /// - [SimulateParams::get_asset_trade_scale] - sets `get_asset_trade_scale`
 /// - [SimulateParams::get_funds_trade_scale] - sets `get_funds_trade_scale`
 ///
 /// # Examples
 /// ```
-/// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
+/// use oscillatorsetups::exchange::chart_data::klines::Intervals;
+/// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{AtrStop, EntryOrder, Execution, FeeCurrency, FeeTier, Futures, PositionMode, SimulateParams, Sizing, Slippage} };
 ///
 /// // TriggerSignal instance
-/// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_close : 1734.3, };
+/// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close : 1734.3, volume:1000.0, };
 /// // vector containing the trigger_signals
 /// let signals = vec![trigger_signal];
 ///
 /// // Set SimulateParams instance
 /// let initial_capital:f64         = 10000.0;
 /// let exchange_fee:Option<f64>    = Some(0.00075); // Assuming a 0.075% exchange fee
+/// let maker_fee:Option<f64>       = Some(0.0005);
+/// let fee_tiers:Vec<FeeTier>      = vec![FeeTier { min_volume: 1_000_000.0, maker: 0.0004, taker: 0.0006 }];
+/// let fee_currency                = FeeCurrency::Quote;
 /// let min_qty:Option<f64>         = Some(0.01);
 /// let min_price:Option<f64>       = Some(10.0);
 /// let asset_scale:u32             = 8;
 /// let funds_scale:u32             = 8;
+/// let position_mode               = PositionMode::LongShort;
+/// let stop_loss:Option<f64>       = Some(0.02);
+/// let take_profit:Option<f64>     = Some(0.05);
+/// let atr_stop:Option<AtrStop>    = Some(AtrStop { length: 14, multiple: 3.0 });
+/// let trailing_stop:Option<f64>   = None;
+/// let sizing                      = Sizing::EquityFraction(0.5);
+/// let slippage                    = Slippage::FixedBps(2.0);
+/// let spread:Option<f64>          = Some(0.0004);
+/// let execution                   = Execution::NextClose;
+/// let entry_order                 = EntryOrder::Limit { offset: 0.001, bars: 3 };
+/// let futures:Option<Futures>     = None;
+/// let interval                    = Intervals::H4;
 ///
 /// // Create SimulateParams instance
-/// let params = SimulateParams { signals, initial_capital, exchange_fee, min_qty, min_price, asset_scale, funds_scale, };
+/// let params = SimulateParams {
+///     signals, initial_capital, exchange_fee, maker_fee, fee_tiers, fee_currency, min_qty, min_price, asset_scale, funds_scale, position_mode,
+///     stop_loss, take_profit, atr_stop, trailing_stop, sizing, slippage, spread, execution, entry_order, futures, interval,
+/// };
 ///
 /// assert_eq!(params.signals[0].signal_in, 10f64);
 /// assert_eq!(params.signals[0].signal_out, 9f64);
 /// assert_eq!(params.signals[0].time_open, 1689294600000u64);
 /// assert_eq!(params.signals[0].time_close, 1689295499999u64);
 /// assert_eq!(params.signals[0].price_open, 1639.26f64);
+/// assert_eq!(params.signals[0].price_high, 1734.3f64);
+/// assert_eq!(params.signals[0].price_low, 1639.26f64);
 /// assert_eq!(params.signals[0].price_close, 1734.3f64);
 /// assert_eq!(params.initial_capital, 10000f64);
 /// assert_eq!(params.exchange_fee, Some(0.00075f64));
+/// assert_eq!(params.maker_fee, Some(0.0005f64));
+/// assert_eq!(params.fee_tiers[0].min_volume, 1_000_000f64);
+/// assert_eq!(params.fee_currency, FeeCurrency::Quote);
 /// assert_eq!(params.min_qty, Some(0.01f64));
 /// assert_eq!(params.min_price, Some(10f64));
 /// assert_eq!(params.asset_scale, 8u32);
 /// assert_eq!(params.funds_scale, 8u32);
+/// assert_eq!(params.position_mode, PositionMode::LongShort);
+/// assert_eq!(params.stop_loss, Some(0.02f64));
+/// assert_eq!(params.take_profit, Some(0.05f64));
+/// assert_eq!(params.atr_stop, Some(AtrStop { length: 14, multiple: 3.0 }));
+/// assert_eq!(params.trailing_stop, None);
+/// assert_eq!(params.sizing, Sizing::EquityFraction(0.5));
+/// assert_eq!(params.slippage, Slippage::FixedBps(2.0));
+/// assert_eq!(params.spread, Some(0.0004f64));
+/// assert_eq!(params.execution, Execution::NextClose);
+/// assert_eq!(params.entry_order, EntryOrder::Limit { offset: 0.001, bars: 3 });
+/// assert_eq!(params.futures, None);
+/// assert_eq!(params.interval, Intervals::H4);
 /// ```
 pub struct SimulateParams {
     pub signals         : Vec<TriggerSignal>,
     pub initial_capital : f64,
     pub exchange_fee    : Option<f64>,
+    pub maker_fee       : Option<f64>,
+    pub fee_tiers       : Vec<FeeTier>,
+    pub fee_currency    : FeeCurrency,
     pub min_qty         : Option<f64>,
     pub min_price       : Option<f64>,
     pub asset_scale     : u32,
     pub funds_scale     : u32,
+    pub position_mode   : PositionMode,
+    pub stop_loss       : Option<f64>,
+    pub take_profit     : Option<f64>,
+    pub atr_stop        : Option<AtrStop>,
+    pub trailing_stop   : Option<f64>,
+    pub sizing          : Sizing,
+    pub slippage        : Slippage,
+    pub spread          : Option<f64>,
+    pub execution       : Execution,
+    pub entry_order     : EntryOrder,
+    pub futures         : Option<Futures>,
+    pub interval        : Intervals,
 }
 
 impl SimulateParams {
@@ -73,10 +162,10 @@ impl SimulateParams {
     /// # Examples
     ///
     /// ```
-    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{EntryOrder, Execution, FeeCurrency, PositionMode, SimulateParams, Sizing, Slippage} };
     ///
     /// // TriggerSignal instance
-    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_close:1734.3, };
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
     ///
     /// // vector containing the trigger_signals
     /// let signals = vec![trigger_signal];
@@ -85,21 +174,54 @@ impl SimulateParams {
     /// // Assert against default values
     /// assert_eq!(params.initial_capital, 1000.0);
     /// assert_eq!(params.exchange_fee, None);
+    /// assert_eq!(params.maker_fee, None);
+    /// assert!(params.fee_tiers.is_empty());
+    /// assert_eq!(params.fee_currency, FeeCurrency::Native);
     /// assert_eq!(params.min_qty, None);
     /// assert_eq!(params.min_price, None);
     /// assert_eq!(params.asset_scale, 8);
     /// assert_eq!(params.funds_scale, 8);
+    /// assert_eq!(params.position_mode, PositionMode::LongOnly);
+    /// assert_eq!(params.stop_loss, None);
+    /// assert_eq!(params.take_profit, None);
+    /// assert_eq!(params.atr_stop, None);
+    /// assert_eq!(params.trailing_stop, None);
+    /// assert_eq!(params.sizing, Sizing::AllFunds);
+    /// assert_eq!(params.slippage, Slippage::Zero);
+    /// assert_eq!(params.spread, None);
+    /// assert_eq!(params.execution, Execution::NextOpen);
+    /// assert_eq!(params.entry_order, EntryOrder::Market);
+    /// assert_eq!(params.futures, None);
+    /// assert_eq!(params.interval.value(), 86400);
     /// ```
     pub fn new(signals:Vec<TriggerSignal>) -> Self {
         SimulateParams { signals,
             initial_capital     : 1000.0,
             exchange_fee        : None,
+            maker_fee           : None,
+            fee_tiers           : vec![],
+            fee_currency        : FeeCurrency::Native,
 
             min_qty     : None,
             min_price   : None,
 
             asset_scale : 8,
-            funds_scale : 8
+            funds_scale : 8,
+
+            position_mode   : PositionMode::LongOnly,
+
+            stop_loss       : None,
+            take_profit     : None,
+            atr_stop        : None,
+            trailing_stop   : None,
+
+            sizing          : Sizing::AllFunds,
+            slippage        : Slippage::Zero,
+            spread          : None,
+            execution       : Execution::NextOpen,
+            entry_order     : EntryOrder::Market,
+            futures         : None,
+            interval        : Intervals::D1,
         }
     }
 
@@ -108,7 +230,7 @@ impl SimulateParams {
     /// ```
     /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
     ///
-    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_close:1734.3, };
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
     /// let signals = vec![trigger_signal];
     /// let params = SimulateParams::new(signals).capital( 100000.0 );
     /// // Assert against new values
@@ -121,7 +243,7 @@ impl SimulateParams {
     /// ```
     /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
     ///
-    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_close:1734.3, };
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
     /// let signals = vec![trigger_signal];
     /// let params = SimulateParams::new(signals).exchange_fee( Some(0.00075) );
     /// // Assert against new values
@@ -129,12 +251,51 @@ impl SimulateParams {
     ///```
     pub fn exchange_fee(mut self, exchange_fee: Option<f64>) -> Self { self.exchange_fee = exchange_fee;self }
 
+    /// set optional `maker_fee`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).maker_fee( Some(0.0005) );
+    /// // Assert against new values
+    /// assert_eq!(params.maker_fee, Some(0.0005f64));
+    ///```
+    pub fn maker_fee(mut self, maker_fee: Option<f64>) -> Self { self.maker_fee = maker_fee;self }
+
+    /// set optional `fee_tiers`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{FeeTier, SimulateParams} };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).fee_tiers( vec![FeeTier { min_volume: 1_000_000.0, maker: 0.0004, taker: 0.0006 }] );
+    /// // Assert against new values
+    /// assert_eq!(params.fee_tiers[0].min_volume, 1_000_000f64);
+    ///```
+    pub fn fee_tiers(mut self, fee_tiers: Vec<FeeTier>) -> Self { self.fee_tiers = fee_tiers;self }
+
+    /// set optional `fee_currency`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{FeeCurrency, SimulateParams} };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).fee_currency( FeeCurrency::Quote );
+    /// // Assert against new values
+    /// assert_eq!(params.fee_currency, FeeCurrency::Quote);
+    ///```
+    pub fn fee_currency(mut self, fee_currency: FeeCurrency) -> Self { self.fee_currency = fee_currency;self }
+
     /// set optional `min_qty`
     /// # Example
     /// ```
     /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
     ///
-    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_close:1734.3, };
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
     /// let signals = vec![trigger_signal];
     /// let params = SimulateParams::new(signals).min_qty( Some(10.0) );
     /// // Assert against new values
@@ -147,7 +308,7 @@ impl SimulateParams {
     /// ```
     /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
     ///
-    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_close:1734.3, };
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
     /// let signals = vec![trigger_signal];
     /// let params = SimulateParams::new(signals).min_price( Some(0.01) );
     /// // Assert against new values
@@ -161,6 +322,163 @@ impl SimulateParams {
     /// set optional `funds_scale`
     pub fn funds_scale(mut self, funds_scale: u32) -> Self {self.funds_scale = funds_scale; self }
 
+    /// set optional `position_mode`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{PositionMode, SimulateParams} };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).position_mode( PositionMode::LongShort );
+    /// // Assert against new values
+    /// assert_eq!(params.position_mode, PositionMode::LongShort);
+    ///```
+    pub fn position_mode(mut self, position_mode: PositionMode) -> Self {self.position_mode = position_mode; self }
+
+    /// set optional `stop_loss`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).stop_loss( Some(0.02) );
+    /// // Assert against new values
+    /// assert_eq!(params.stop_loss, Some(0.02f64));
+    ///```
+    pub fn stop_loss(mut self, stop_loss: Option<f64>) -> Self {self.stop_loss = stop_loss; self }
+
+    /// set optional `take_profit`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).take_profit( Some(0.05) );
+    /// // Assert against new values
+    /// assert_eq!(params.take_profit, Some(0.05f64));
+    ///```
+    pub fn take_profit(mut self, take_profit: Option<f64>) -> Self {self.take_profit = take_profit; self }
+
+    /// set optional `atr_stop`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{AtrStop, SimulateParams} };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).atr_stop( Some(AtrStop { length: 14, multiple: 3.0 }) );
+    /// // Assert against new values
+    /// assert_eq!(params.atr_stop, Some(AtrStop { length: 14, multiple: 3.0 }));
+    ///```
+    pub fn atr_stop(mut self, atr_stop: Option<AtrStop>) -> Self {self.atr_stop = atr_stop; self }
+
+    /// set optional `trailing_stop`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).trailing_stop( Some(0.03) );
+    /// // Assert against new values
+    /// assert_eq!(params.trailing_stop, Some(0.03f64));
+    ///```
+    pub fn trailing_stop(mut self, trailing_stop: Option<f64>) -> Self {self.trailing_stop = trailing_stop; self }
+
+    /// set optional `sizing`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{SimulateParams, Sizing} };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).sizing( Sizing::EquityFraction(0.5) );
+    /// // Assert against new values
+    /// assert_eq!(params.sizing, Sizing::EquityFraction(0.5));
+    ///```
+    pub fn sizing(mut self, sizing: Sizing) -> Self {self.sizing = sizing; self }
+
+    /// set optional `slippage`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{SimulateParams, Slippage} };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).slippage( Slippage::FixedBps(2.0) );
+    /// // Assert against new values
+    /// assert_eq!(params.slippage, Slippage::FixedBps(2.0));
+    ///```
+    pub fn slippage(mut self, slippage: Slippage) -> Self {self.slippage = slippage; self }
+
+    /// set optional `spread`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).spread( Some(0.0004) );
+    /// // Assert against new values
+    /// assert_eq!(params.spread, Some(0.0004f64));
+    ///```
+    pub fn spread(mut self, spread: Option<f64>) -> Self {self.spread = spread; self }
+
+    /// set optional `execution`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{Execution, SimulateParams} };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).execution( Execution::NextClose );
+    /// // Assert against new values
+    /// assert_eq!(params.execution, Execution::NextClose);
+    ///```
+    pub fn execution(mut self, execution: Execution) -> Self {self.execution = execution; self }
+
+    /// set optional `entry_order`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{EntryOrder, SimulateParams} };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).entry_order( EntryOrder::Limit { offset: 0.001, bars: 3 } );
+    /// // Assert against new values
+    /// assert_eq!(params.entry_order, EntryOrder::Limit { offset: 0.001, bars: 3 });
+    ///```
+    pub fn entry_order(mut self, entry_order: EntryOrder) -> Self {self.entry_order = entry_order; self }
+
+    /// set optional `futures`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{Futures, SimulateParams} };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).futures( Some(Futures { leverage: 5.0, maintenance_margin: 0.005, funding: vec![] }) );
+    /// // Assert against new values
+    /// assert_eq!(params.futures.unwrap().leverage, 5f64);
+    ///```
+    pub fn futures(mut self, futures: Option<Futures>) -> Self {self.futures = futures; self }
+
+    /// set optional `interval`
+    /// # Example
+    /// ```
+    /// use oscillatorsetups::exchange::chart_data::klines::Intervals;
+    /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
+    ///
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
+    /// let signals = vec![trigger_signal];
+    /// let params = SimulateParams::new(signals).interval( Intervals::H4 );
+    /// // Assert against new values
+    /// assert_eq!(params.interval, Intervals::H4);
+    ///```
+    pub fn interval(mut self, interval: Intervals) -> Self {self.interval = interval; self }
+
     /// Returns the scale (number of decimal places) of the minimum quantity (`min_qty`) property
     /// for the asset trade. This is helpful to determine the precision at which the asset
     /// trades occur. If `min_qty` is not set, the function returns `None`.
@@ -169,7 +487,7 @@ impl SimulateParams {
     /// ```
     /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
     ///
-    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_close:1734.3, };
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
     /// let signals = vec![trigger_signal];
     /// let params = SimulateParams::new(signals).min_qty(Some(0.001));
     /// assert_eq!(params.get_asset_trade_scale(), Some(3));  // 3 decimal places in 0.001
@@ -191,7 +509,7 @@ impl SimulateParams {
     /// ```
     /// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::SimulateParams };
     ///
-    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_close:1734.3, };
+    /// let trigger_signal = TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1689294600000, time_close:1689295499999, price_open:1639.26, price_high:1734.3, price_low:1639.26, price_close:1734.3, volume:1000.0, };
     /// let signals = vec![trigger_signal];
     /// let params = SimulateParams::new(signals).min_price(Some(0.01));
     /// assert_eq!(params.get_funds_trade_scale(), Some(2));  // 2 decimal places in 0.01
@@ -207,6 +525,491 @@ impl SimulateParams {
 
 }
 
+/// Which side(s) the simulation trades on, see [`SimulateParams::position_mode`].
+///
+/// # Variants
+/// - `LongOnly`: The default. Enters long while `signal_in > signal_out`, exits while `signal_in < signal_out`.
+/// - `ShortOnly`: Enters short while `signal_in < signal_out`, exits while `signal_in > signal_out`.
+/// - `LongShort`: Always in the market once the first position is taken, each crossover closes the open position
+///   and reverses into the opposite side at the same price.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PositionMode {
+    #[default]
+    LongOnly,
+    ShortOnly,
+    LongShort,
+}
+
+impl PositionMode {
+    /// Returns whether positions in `direction` may be opened.
+    fn allows(&self, direction: Direction) -> bool {
+        match self {
+            PositionMode::LongOnly  => direction == Direction::Long,
+            PositionMode::ShortOnly => direction == Direction::Short,
+            PositionMode::LongShort => true,
+        }
+    }
+}
+
+/// How much of the funds each entry of [`simulate`] commits, see [`SimulateParams::sizing`].
+///
+/// The stake is the amount of funds a position is sized with, fees included. It is capped by the funds available,
+/// whatever is not staked stays in funds. The equity the stakes are derived from is marked to market at the entry price.
+///
+/// # Variants
+/// - `AllFunds`: The default. Every entry stakes all available funds.
+/// - `FixedQuote`: Every entry stakes the same amount of funds.
+/// - `EquityFraction`: Every entry stakes this fraction of the equity, e.g. 0.25 for 25%.
+/// - `VolatilityTarget`: Sizes the position so a move of one ATR over `atr_length` ticks changes the equity by the fraction `risk`.
+///   No position is taken until the ATR is available.
+/// - `Kelly`: Stakes `fraction` of the Kelly criterion `W - (1 - W) / R`, with `W` the win rate and `R` the ratio of the
+///   average win to the average loss of the trades closed so far. Until `min_trades` trades are closed, `fraction` of the
+///   equity is staked. The criterion is floored at `min_criterion`, so a losing streak shrinks the stakes but keeps trading,
+///   and the criterion can recover. With a `min_criterion` of 0 no position is taken while the edge is negative.
+///
+/// The `buy_and_hold_return` of [`PnL`] invests the stake of the first entry, which is the whole initial capital
+/// for the `VolatilityTarget` and `Kelly` sizings as it depends on the course of the simulation.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{backtest, SimulateParams, Sizing} };
+///
+/// // Entered at every odd, exited at every even tick: two losing trades, then three winning ones
+/// let prices = [100.0, 100.0, 90.0, 90.0, 81.0, 81.0, 89.1, 89.1, 98.01, 98.01, 107.811, 107.811];
+/// let signals = || prices.iter().enumerate().map(|(ix, &price)| TriggerSignal {
+///     signal_in: if ix % 2 == 0 { 10.0 } else { 8.0 }, signal_out:9.0, time_open:ix as u64, time_close:ix as u64,
+///     price_open:price, price_high:price, price_low:price, price_close:price, volume:1000.0,
+/// }).collect();
+/// let stakes = |sizing| -> Vec<f64> {
+///     backtest(SimulateParams::new(signals()).sizing(sizing)).trades.iter().map(|trade| (trade.quantity * trade.entry_price * 100.0).round() / 100.0).collect()
+/// };
+///
+/// assert_eq!(stakes(Sizing::FixedQuote(250.0)), vec![250.0; 5]);
+/// assert_eq!(stakes(Sizing::EquityFraction(0.5))[..2], [500.0, 475.0]);
+///
+/// // Both losses leave no edge, the stakes fall to the floor of 10% of half the equity instead of stopping
+/// let kelly = stakes(Sizing::Kelly { fraction: 0.5, min_trades: 2, min_criterion: 0.1 });
+/// assert_eq!(kelly.len(), 5);
+/// assert_eq!(kelly[..3], [500.0, 475.0, 45.12]);
+///
+/// let stalled = stakes(Sizing::Kelly { fraction: 0.5, min_trades: 2, min_criterion: 0.0 });
+/// assert_eq!(stalled.len(), 2);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Sizing {
+    #[default]
+    AllFunds,
+    FixedQuote(f64),
+    EquityFraction(f64),
+    VolatilityTarget { atr_length: u16, risk: f64 },
+    Kelly { fraction: f64, min_trades: usize, min_criterion: f64 },
+}
+
+impl Sizing {
+    /// Returns the stake of the first entry out of `capital`, invested by the buy-and-hold benchmark.
+    fn initial_stake(&self, capital: f64) -> f64 {
+        match *self {
+            Sizing::FixedQuote(stake)           => stake.min(capital),
+            Sizing::EquityFraction(fraction)    => capital * fraction.min(1.0),
+            _                                   => capital,
+        }
+    }
+}
+
+/// How far the fills of [`simulate`] deviate from the quoted price against the trade, see [`SimulateParams::slippage`].
+///
+/// Buys fill above, sells below the quoted price, which is the `price_open` of a tick or the level of a stop.
+/// The slippage adds to half of the [`SimulateParams::spread`]. Resting orders, the take-profit and [`EntryOrder::Limit`] entries,
+/// fill at their limit instead. The `buy_and_hold_return` of [`PnL`] pays the slippage and spread of its entry at the
+/// open of the first tick and its exit at the close of the last one.
+///
+/// # Variants
+/// - `Zero`: The default. Fills at the quoted price.
+/// - `FixedBps`: A fixed number of basis points of the price.
+/// - `RangeFraction`: A fraction of the high-low range of the tick filled in.
+/// - `VolumeParticipation`: `impact` times the square root of the quantity traded relative to the `volume` of the tick,
+///   as a fraction of the price. The participation is capped at the whole volume.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{backtest, SimulateParams, Slippage} };
+///
+/// // The 10 units bought at 100 are a quarter of the volume of the entry tick, but more than the volume of the exit tick
+/// let signals = || vec![
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:0, time_close:1, price_open:100.0, price_high:100.0, price_low:100.0, price_close:100.0, volume:1000.0, },
+///     TriggerSignal { signal_in:8.0, signal_out:9.0, time_open:2, time_close:3, price_open:100.0, price_high:106.0, price_low:100.0, price_close:105.0, volume:40.0, },
+///     TriggerSignal { signal_in:8.0, signal_out:9.0, time_open:4, time_close:5, price_open:110.0, price_high:110.0, price_low:110.0, price_close:110.0, volume:5.0, },
+/// ];
+/// let volume = Slippage::VolumeParticipation { impact: 0.01 };
+///
+/// let slipped = backtest(SimulateParams::new(signals()).slippage(volume).spread(Some(0.002)));
+/// let trade = &slipped.trades[0];
+///
+/// // 1% times the square root of the participation, and half of the 20 bps spread against each fill
+/// assert_eq!(trade.entry_price, 100.0 + 0.5 + 0.1);
+/// assert_eq!(trade.exit_price, 110.0 - 1.1 - 0.11);
+///
+/// // So does the buy-and-hold benchmark, entered at 100.2 and exited at 108.79 instead of 110
+/// assert_eq!(slipped.pnl.buy_and_hold_return, 85.73);
+///
+/// // The take-profit 5% above the quoted entry rests at its limit, it is not slipped
+/// let target = backtest(SimulateParams::new(signals()).slippage(volume).take_profit(Some(0.05)));
+/// assert_eq!(target.trades[0].exit_price, 105.0);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Slippage {
+    #[default]
+    Zero,
+    FixedBps(f64),
+    RangeFraction(f64),
+    VolumeParticipation { impact: f64 },
+}
+
+impl Slippage {
+    /// Returns the adverse price move of filling `qty` at `price` within `tick`.
+    fn offset(&self, price: f64, qty: f64, tick: &TriggerSignal) -> f64 {
+        match *self {
+            Slippage::Zero                      => 0.0,
+            Slippage::FixedBps(bps)             => price * bps / 10_000.0,
+            Slippage::RangeFraction(fraction)   => (tick.price_high - tick.price_low) * fraction,
+            Slippage::VolumeParticipation { impact } => {
+                let participation = if tick.volume > 0.0 { (qty / tick.volume).min(1.0) } else { 1.0 };
+                price * impact * participation.sqrt()
+            },
+        }
+    }
+}
+
+/// When the orders staged by the signals of [`simulate`] are filled, see [`SimulateParams::execution`].
+///
+/// The signals are evaluated at the close of every tick. Protective exits are checked against the range of every tick
+/// the position is held through, including the tick it is entered on if filled at its open. Positions filled at a close,
+/// or by a limit entry within the range of the tick, are first checked on the following tick.
+///
+/// # Variants
+/// - `SignalClose`: At the `price_close` of the tick the signal was given on.
+/// - `NextOpen`: The default. At the `price_open` of the following tick.
+/// - `NextClose`: At the `price_close` of the following tick.
+/// - `NextVwap`: At the average of `price_open`, `price_high`, `price_low` and `price_close` of the following tick,
+///   approximating its volume-weighted average price.
+///
+/// The trades are timed like they are priced, at the `time_open` of the tick with `NextOpen` and at its `time_close`
+/// otherwise. Protective exits and limit entries, filled within the range of a tick, are timed at its `time_open`.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::{ models::{ExitReason, TriggerSignal}, pnl::{backtest, Execution, SimulateParams} };
+///
+/// // Bullish crossover at the close of 100, the next ticks gap away from the previous close
+/// let signals = || vec![
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:0, time_close:1, price_open:98.0, price_high:100.0, price_low:97.0, price_close:100.0, volume:1000.0, },
+///     TriggerSignal { signal_in:8.0, signal_out:9.0, time_open:2, time_close:3, price_open:101.0, price_high:106.0, price_low:99.0, price_close:104.0, volume:1000.0, },
+///     TriggerSignal { signal_in:8.0, signal_out:9.0, time_open:4, time_close:5, price_open:103.0, price_high:105.0, price_low:100.0, price_close:102.0, volume:1000.0, },
+///     TriggerSignal { signal_in:8.0, signal_out:9.0, time_open:6, time_close:7, price_open:102.0, price_high:102.0, price_low:102.0, price_close:102.0, volume:1000.0, },
+/// ];
+/// let fills = |execution| {
+///     let trade = backtest(SimulateParams::new(signals()).execution(execution)).trades[0].clone();
+///     ((trade.entry_price, trade.exit_price), (trade.entry_time, trade.exit_time))
+/// };
+///
+/// assert_eq!(fills(Execution::SignalClose), ((100.0, 104.0), (1, 3)));
+/// assert_eq!(fills(Execution::NextOpen), ((101.0, 103.0), (2, 4)));
+/// assert_eq!(fills(Execution::NextClose), ((104.0, 102.0), (3, 5)));
+/// assert_eq!(fills(Execution::NextVwap), ((102.5, 102.5), (3, 5)));
+///
+/// // Entered at the close of 104, the stop at 101.92 is not checked against the earlier low of 99 of the same tick
+/// let stopped = backtest(SimulateParams::new(signals()).execution(Execution::NextClose).stop_loss(Some(0.02))).trades;
+/// assert_eq!((stopped[0].exit_time, stopped[0].exit_price, stopped[0].exit_reason), (4, 101.92, ExitReason::StopLoss));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Execution {
+    SignalClose,
+    #[default]
+    NextOpen,
+    NextClose,
+    NextVwap,
+}
+
+impl Execution {
+    /// Returns the price the orders are filled at within `tick`.
+    fn price(&self, tick: &TriggerSignal) -> f64 {
+        match self {
+            Execution::NextOpen                             => tick.price_open,
+            Execution::SignalClose | Execution::NextClose   => tick.price_close,
+            Execution::NextVwap                             => (tick.price_open + tick.price_high + tick.price_low + tick.price_close) / 4.0,
+        }
+    }
+
+    /// Returns the time the orders are filled at within `tick`, the `time_close` of fills at its close or VWAP.
+    fn time(&self, tick: &TriggerSignal) -> u64 {
+        match self {
+            Execution::NextOpen => tick.time_open,
+            _                   => tick.time_close,
+        }
+    }
+
+    /// Returns the order the steps of a tick are carried out in.
+    /// A resting limit order is filled before the signals can cancel it, and before the protective exits of the tick.
+    fn steps(&self) -> [Step; 4] {
+        match self {
+            Execution::NextOpen     => [Step::Fill, Step::Limit, Step::Hold, Step::Signal],
+            Execution::SignalClose  => [Step::Limit, Step::Hold, Step::Signal, Step::Fill],
+            Execution::NextClose | Execution::NextVwap => [Step::Limit, Step::Hold, Step::Fill, Step::Signal],
+        }
+    }
+}
+
+/// How the positions of [`simulate`] are entered, see [`SimulateParams::entry_order`].
+///
+/// # Variants
+/// - `Market`: The default. Entries are filled at the price of the [`Execution`], slipped and charged the taker rate.
+/// - `Limit`: A limit order is placed at the close of the tick the signals were given on, `offset` below it for a long and
+///   above it for a short entry, e.g. 0.002 for 0.2%. It fills at the limit on the first of the following `bars` ticks whose
+///   `price_low` (`price_high` for a short) reaches it, at the open if the tick gaps through it, without slippage or spread
+///   and charged the [`SimulateParams::maker_fee`]. It is cancelled when the signals turn, and once it expires unfilled
+///   the side is not entered until the signals turn. Whatever the [`Execution`], a tick reaching the limit fills it
+///   before its signals are evaluated, so signals turning on that tick exit the position instead of cancelling the order.
+///   In every [`Execution`] alike, the protective exits only guard the tick of the fill if it gapped through the limit
+///   and filled at the open, and a limit entry reversing a position waits until the position is closed.
+///
+/// Exits are not affected, they are filled by the [`Execution`].
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::{ models::{ExitReason, TriggerSignal}, pnl::{backtest, EntryOrder, Execution, SimulateParams} };
+///
+/// // Bullish crossover at 100, the price dips to 98 two ticks later
+/// let signals = || vec![
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:0, time_close:1, price_open:100.0, price_high:100.0, price_low:100.0, price_close:100.0, volume:1000.0, },
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:2, time_close:3, price_open:100.0, price_high:101.0, price_low:99.5, price_close:100.0, volume:1000.0, },
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:4, time_close:5, price_open:100.0, price_high:100.0, price_low:98.0, price_close:99.0, volume:1000.0, },
+///     TriggerSignal { signal_in:8.0, signal_out:9.0, time_open:6, time_close:7, price_open:99.0, price_high:105.0, price_low:99.0, price_close:105.0, volume:1000.0, },
+///     TriggerSignal { signal_in:8.0, signal_out:9.0, time_open:8, time_close:9, price_open:105.0, price_high:105.0, price_low:105.0, price_close:105.0, volume:1000.0, },
+/// ];
+///
+/// let limit = backtest(SimulateParams::new(signals()).entry_order(EntryOrder::Limit { offset: 0.01, bars: 2 }));
+/// assert_eq!((limit.trades[0].entry_time, limit.trades[0].entry_price), (4, 99.0));
+///
+/// // Expired unfilled, the side is not entered until the signals turn
+/// let expired = backtest(SimulateParams::new(signals()).entry_order(EntryOrder::Limit { offset: 0.01, bars: 1 }));
+/// assert!(expired.trades.is_empty());
+///
+/// // Filled at 99 within the tick the signals turn on, then exited at its close
+/// let turning = vec![
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:0, time_close:1, price_open:100.0, price_high:100.0, price_low:100.0, price_close:100.0, volume:1000.0, },
+///     TriggerSignal { signal_in:8.0, signal_out:9.0, time_open:2, time_close:3, price_open:100.0, price_high:101.0, price_low:98.5, price_close:100.5, volume:1000.0, },
+///     TriggerSignal { signal_in:8.0, signal_out:9.0, time_open:4, time_close:5, price_open:100.5, price_high:100.5, price_low:100.5, price_close:100.5, volume:1000.0, },
+/// ];
+/// let params = SimulateParams::new(turning).execution(Execution::SignalClose).entry_order(EntryOrder::Limit { offset: 0.01, bars: 2 });
+/// let trade = &backtest(params).trades[0];
+/// assert_eq!((trade.entry_price, trade.exit_price), (99.0, 100.5));
+/// assert_eq!((trade.entry_time, trade.exit_time), (2, 3));
+///
+/// // The tick after the crossover dips to 97 after the fill at 99, or opens at 98 below the limit, then dips to 96
+/// let dip = |price_open:f64, price_low:f64| vec![
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:0, time_close:1, price_open:100.0, price_high:100.0, price_low:100.0, price_close:100.0, volume:1000.0, },
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:2, time_close:3, price_open, price_high:100.0, price_low, price_close:99.5, volume:1000.0, },
+///     TriggerSignal { signal_in:8.0, signal_out:9.0, time_open:4, time_close:5, price_open:99.5, price_high:99.5, price_low:99.5, price_close:99.5, volume:1000.0, },
+///     TriggerSignal { signal_in:8.0, signal_out:9.0, time_open:6, time_close:7, price_open:99.5, price_high:99.5, price_low:99.5, price_close:99.5, volume:1000.0, },
+/// ];
+/// let exit = |signals, execution| {
+///     let params = SimulateParams::new(signals).execution(execution).entry_order(EntryOrder::Limit { offset: 0.01, bars: 2 }).stop_loss(Some(0.01));
+///     let trade = backtest(params).trades[0].clone();
+///     (trade.exit_price, trade.exit_reason)
+/// };
+/// for execution in [Execution::NextOpen, Execution::SignalClose, Execution::NextClose] {
+///     // The 1% stop under the fill at 99 is not checked against the low of the same tick, filled within its range
+///     assert_eq!(exit(dip(100.0, 97.0), execution).1, ExitReason::Signal);
+///     // Filled at the open of 98, the range follows the fill and hits the stop at 97.02
+///     assert_eq!(exit(dip(98.0, 96.0), execution), (97.02, ExitReason::StopLoss));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum EntryOrder {
+    #[default]
+    Market,
+    Limit { offset: f64, bars: usize },
+}
+
+/// Settings of the perpetual futures traded by [`simulate`] instead of spot, see [`SimulateParams::futures`].
+///
+/// The stake of the [`Sizing`] is the initial margin of a position, which is `leverage` times larger, except for
+/// [`Sizing::FixedQuote`] and [`Sizing::VolatilityTarget`], which size the position itself. The margin is isolated:
+/// a position is liquidated once its losses leave less than `maintenance_margin` of its notional, checked intrabar
+/// against `price_low` (`price_high` for a short) like a stop, see [`ExitReason::Liquidation`]. A tick opening beyond the
+/// liquidation price fills at its open, so the loss may exceed the margin. Fees are charged in quote on both sides.
+///
+/// At every [`FundingRate`] falling within a tick a position is held through, the rate of its notional at `price_open`
+/// is paid by longs to shorts, or the other way around if negative. Funding is paid out of the margin, moving the liquidation
+/// price along. The `buy_and_hold_return` of [`PnL`] stays unleveraged.
+///
+/// # Fields
+/// - `leverage`: The notional of a position relative to its margin, e.g. 5.0 for 5x.
+/// - `maintenance_margin`: The margin a position has to keep, as a fraction of its notional, e.g. 0.005 for 0.5%.
+/// - `funding`: The funding rates, ordered by time.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::{ models::{ExitReason, TriggerSignal}, pnl::{backtest, FundingRate, Futures, SimulateParams, Sizing} };
+///
+/// // Bullish crossover at 100, the price dips to 85 on the next tick, the funding is paid at its open
+/// let signals = || vec![
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:0, time_close:999, price_open:100.0, price_high:100.0, price_low:100.0, price_close:100.0, volume:1000.0, },
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1000, time_close:1999, price_open:100.0, price_high:102.0, price_low:85.0, price_close:95.0, volume:1000.0, },
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:2000, time_close:2999, price_open:95.0, price_high:95.0, price_low:95.0, price_close:95.0, volume:1000.0, },
+/// ];
+/// let futures = |leverage| Futures { leverage, maintenance_margin: 0.005, funding: vec![FundingRate { time: 1000, rate: 0.0001 }] };
+///
+/// let leveraged = backtest(SimulateParams::new(signals()).futures(Some(futures(3.0))));
+/// assert_eq!(leveraged.trades[0].quantity, 30.0);
+/// assert_eq!(leveraged.trades[0].funding, 0.3);
+/// assert_eq!(leveraged.pnl.net_profit, -150.3);
+/// // The benchmark holds the 10 units the capital buys, without leverage
+/// assert_eq!(leveraged.pnl.buy_and_hold_return, -50.0);
+///
+/// let liquidated = backtest(SimulateParams::new(signals()).futures(Some(futures(10.0))));
+/// assert_eq!(liquidated.trades[0].exit_reason, ExitReason::Liquidation);
+/// assert!(liquidated.pnl.net_profit < -900.0);
+///
+/// // 20% of the equity is a margin of 200 backing a notional of 1000 at 5x, liquidated once the loss of the 10 units
+/// // leaves 0.5% of the notional: at (1000 - 200) / (10 * (1 - 0.005)) = 80.40
+/// let margin = |price_low:f64| {
+///     let mut signals = signals();
+///     signals[1].price_low = price_low;
+///     let futures = Futures { leverage: 5.0, maintenance_margin: 0.005, funding: vec![] };
+///     backtest(SimulateParams::new(signals).futures(Some(futures)).sizing(Sizing::EquityFraction(0.2))).trades[0].clone()
+/// };
+/// assert_eq!(margin(80.5).quantity, 10.0);
+/// assert_eq!(margin(80.5).exit_reason, ExitReason::EndOfData);
+/// assert_eq!(margin(80.3).exit_reason, ExitReason::Liquidation);
+///
+/// // A fixed quote amount is the notional, not the margin
+/// let fixed = backtest(SimulateParams::new(signals()).futures(Some(futures(3.0))).sizing(Sizing::FixedQuote(500.0)));
+/// assert_eq!(fixed.trades[0].quantity, 5.0);
+///
+/// // At 10x, liquidated below 90.45, or 91.46 once 1% funding was paid out of the margin
+/// let funded = Futures { leverage: 10.0, maintenance_margin: 0.005, funding: vec![FundingRate { time: 2000, rate: 0.01 }] };
+/// let dip = vec![
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:0, time_close:999, price_open:100.0, price_high:100.0, price_low:100.0, price_close:100.0, volume:1000.0, },
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1000, time_close:1999, price_open:100.0, price_high:100.0, price_low:99.0, price_close:100.0, volume:1000.0, },
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:2000, time_close:2999, price_open:100.0, price_high:100.0, price_low:91.0, price_close:95.0, volume:1000.0, },
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:3000, time_close:3999, price_open:95.0, price_high:95.0, price_low:95.0, price_close:95.0, volume:1000.0, },
+/// ];
+/// let trades = backtest(SimulateParams::new(dip).futures(Some(funded))).trades;
+/// assert_eq!((trades[0].exit_reason, trades[0].exit_time), (ExitReason::Liquidation, 2000));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Futures {
+    pub leverage            : f64,
+    pub maintenance_margin  : f64,
+    pub funding             : Vec<FundingRate>,
+}
+
+/// A funding rate of [`Futures`], paid at `time` (in milliseconds, like the `time_open` of the ticks).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FundingRate {
+    pub time    : u64,
+    pub rate    : f64,
+}
+
+/// A step of a tick of [`simulate`], ordered by the [`Execution`].
+enum Step {
+    /// Fills the staged exit and market entry
+    Fill,
+    /// Fills the resting limit entry if the range of the tick reaches it, or cancels it once expired
+    Limit,
+    /// Carries the open position through the tick, paying funding and checking the protective exits against its range
+    Hold,
+    /// Evaluates the signals, staging an exit or entry
+    Signal,
+}
+
+/// A stop placed a multiple of the Average True Range away from the entry price, see [`SimulateParams::atr_stop`].
+///
+/// The ATR is calculated over the `price_high`, `price_low` and `price_close` of the signals,
+/// the value of the tick the entry was triggered on is used. Until `length` ticks are available, no ATR stop is placed.
+///
+/// # Fields
+/// - `length`: The number of periods used for the Wilder smoothing of the true range.
+/// - `multiple`: The distance of the stop from the entry price, in ATRs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtrStop {
+    pub length      : u16,
+    pub multiple    : f64,
+}
+
+/// A tier of a volume-based fee schedule, see [`SimulateParams::fee_tiers`].
+///
+/// The traded volume is the quote value of all fills of the last 30 days, measured by the `time_open` of the ticks.
+/// Every fill is charged the rates of the highest tier whose `min_volume` the volume reaches, falling back to
+/// [`SimulateParams::exchange_fee`] and [`SimulateParams::maker_fee`] below the first tier.
+///
+/// # Fields
+/// - `min_volume`: The 30-day traded volume, in quote, from which the tier applies.
+/// - `maker`: The fee rate of fills of resting orders.
+/// - `taker`: The fee rate of market fills and stops.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{backtest, FeeTier, SimulateParams} };
+///
+/// // Two round trips at 100
+/// let tick = |time_open:u64, signal_in:f64| TriggerSignal { signal_in, signal_out:9.0, time_open, time_close:time_open + 1, price_open:100.0, price_high:100.0, price_low:100.0, price_close:100.0, volume:1000.0, };
+/// let signals = vec![tick(0, 10.0), tick(2, 8.0), tick(4, 10.0), tick(6, 8.0), tick(8, 8.0)];
+///
+/// let tiers = vec![
+///     FeeTier { min_volume: 0.0, maker: 0.001, taker: 0.001 },
+///     FeeTier { min_volume: 1500.0, maker: 0.0005, taker: 0.0005 },
+/// ];
+/// let result = backtest(SimulateParams::new(signals).fee_tiers(tiers));
+///
+/// // The second round trip is charged the lower tier
+/// assert_eq!(result.trades[0].fees, 1.997001);
+/// assert_eq!(result.trades[1].fees, 0.9972541225);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeTier {
+    pub min_volume  : f64,
+    pub maker       : f64,
+    pub taker       : f64,
+}
+
+/// The currency the fees of [`simulate`] are paid in, see [`SimulateParams::fee_currency`].
+///
+/// # Variants
+/// - `Native`: The default. Buys pay the fee in quote, long exits in the asset sold, as spot exchanges do.
+/// - `Quote`: Every fee is paid in quote.
+/// - `ThirdAsset`: Fees are paid in a third asset at a `discount` of the rates, e.g. 0.25 for the 25% BNB discount of Binance.
+///   The holding of the third asset is assumed to be topped up from the funds, so the fees are deducted from them at their quote value.
+///
+/// The `buy_and_hold_return` of [`PnL`] is charged the same way.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{backtest, FeeCurrency, SimulateParams} };
+///
+/// let tick = |time_open:u64, signal_in:f64, price:f64| TriggerSignal { signal_in, signal_out:9.0, time_open, time_close:time_open + 1, price_open:price, price_high:price, price_low:price, price_close:price, volume:1000.0, };
+/// // 9.99 bought at 100 for 999 and a fee of 0.999, sold at 200 in a wallet of 2 decimals
+/// let signals = || vec![tick(0, 10.0, 100.0), tick(1, 8.0, 100.0), tick(2, 8.0, 200.0)];
+/// let fees = |fee_currency| {
+///     let result = backtest(SimulateParams::new(signals()).exchange_fee(Some(0.001)).asset_scale(2).fee_currency(fee_currency));
+///     (result.trades[0].fees, result.pnl.buy_and_hold_return)
+/// };
+///
+/// // The fee withheld from the asset leaves only 9.98 to sell, charged 0.00998 of the asset worth 1.996
+/// assert_eq!(fees(FeeCurrency::Native), (2.995, 996.0));
+/// // Charged 0.1% of the 1998 the 9.99 are sold for
+/// assert_eq!(fees(FeeCurrency::Quote), (2.997, 996.0));
+/// assert_eq!(fees(FeeCurrency::ThirdAsset { discount: 0.25 }), (2.997 * 0.75, 996.75));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FeeCurrency {
+    #[default]
+    Native,
+    Quote,
+    ThirdAsset { discount: f64 },
+}
+
 /// Simulates a sequence of trades based on the given parameters and computes key trading performance metrics.
 ///
 /// This function uses the provided simulation parameters to drive a series of buy and sell decisions.
@@ -216,7 +1019,7 @@ impl SimulateParams {
 ///
 /// # Parameters
 /// - `sim_params`: [SimulateParams] The parameters that drive the simulation. This includes data such as price signals,
-///                 fees, initial capital, and trade scaling.
+///   fees, initial capital, and trade scaling.
 ///
 /// # Returns
 /// A [`PnL`] object which encapsulates various trading performance metrics, such as net profit,
@@ -225,183 +1028,1027 @@ impl SimulateParams {
 /// # Notes
 /// The simulation iterates through each "tick" (price point) in the provided signals. Depending on
 /// the relation between the `signal_in` and `signal_out` values of the tick and the current position status,
-/// a buy or sell decision is simulated. The performance metrics are updated based on the outcome
+/// an entry or exit is staged and filled at the open of the next tick, unless timed otherwise by [`SimulateParams::execution`].
+/// Which sides are traded is decided by [`SimulateParams::position_mode`]. The performance metrics are updated based on the outcome
 /// of these simulated trades.
+///
+/// Long positions pay the entry fee in funds and the exit fee in the asset, as spot exchanges do, unless paid otherwise by [`FeeCurrency`].
+/// Short positions borrow the asset, sized as if it was purchased with the available funds, and pay both fees in funds.
+/// With [`PositionMode::ShortOnly`] the `buy_and_hold_return` holds a short position over the whole period instead.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{simulate, PositionMode, SimulateParams} };
+///
+/// // Bearish crossover at 100, then the price falls to 80
+/// let signals = || vec![
+///     TriggerSignal { signal_in:9.0, signal_out:10.0, time_open:0, time_close:1, price_open:100.0, price_high:100.0, price_low:100.0, price_close:100.0, volume:1000.0, },
+///     TriggerSignal { signal_in:9.0, signal_out:10.0, time_open:1, time_close:2, price_open:100.0, price_high:100.0, price_low:80.0, price_close:80.0, volume:1000.0, },
+///     TriggerSignal { signal_in:9.0, signal_out:10.0, time_open:2, time_close:3, price_open:80.0, price_high:80.0, price_low:80.0, price_close:80.0, volume:1000.0, },
+/// ];
+///
+/// let long_only = simulate(SimulateParams::new(signals()));
+/// assert_eq!(long_only.total_closed_trades, 0);
+///
+/// let short_only = simulate(SimulateParams::new(signals()).position_mode(PositionMode::ShortOnly));
+/// assert_eq!(short_only.total_closed_trades, 1);
+/// assert_eq!(short_only.net_profit, 200.0);
+/// assert_eq!(short_only.buy_and_hold_return, 200.0);
+/// ```
+///
+/// Protective exits fill intrabar, and the side stopped out is not re-entered until the signals turn:
+/// ```
+/// use oscillatorsetups::pnl_simulator::{ models::{ExitReason, TriggerSignal}, pnl::{backtest, simulate, SimulateParams} };
+///
+/// // Bullish crossover at 100, the next tick trades between 95 and 101
+/// let signals = || vec![
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:0, time_close:1, price_open:100.0, price_high:100.0, price_low:100.0, price_close:100.0, volume:1000.0, },
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:1, time_close:2, price_open:100.0, price_high:101.0, price_low:95.0, price_close:97.0, volume:1000.0, },
+///     TriggerSignal { signal_in:10.0, signal_out:9.0, time_open:2, time_close:3, price_open:97.0, price_high:97.0, price_low:97.0, price_close:97.0, volume:1000.0, },
+/// ];
+///
+/// let stopped = simulate(SimulateParams::new(signals()).stop_loss(Some(0.04)));
+/// assert_eq!(stopped.total_closed_trades, 1);
+/// assert_eq!(stopped.net_profit, -40.0);
+/// assert_eq!((stopped.max_drawdown, stopped.max_drawdown_percent), (40.0, 4.0));
+/// assert_eq!(stopped.max_drawdown_duration, 2);
+/// assert_eq!(stopped.exposure, 33.33);
+///
+/// let trades = backtest(SimulateParams::new(signals()).stop_loss(Some(0.04))).trades;
+/// assert_eq!((trades[0].exit_price, trades[0].exit_reason), (96.0, ExitReason::StopLoss));
+///
+/// let target = simulate(SimulateParams::new(signals()).take_profit(Some(0.01)));
+/// assert_eq!(target.net_profit, 10.0);
+///
+/// // Signals without a direction in between do not count as a turn
+/// let mut signals = signals();
+/// signals[2].signal_in = 9.0;
+/// signals.extend((3..6).map(|time_open| TriggerSignal { signal_in:10.0, signal_out:9.0, time_open, time_close:time_open + 1, price_open:97.0, price_high:97.0, price_low:97.0, price_close:97.0, volume:1000.0, }));
+/// let stopped = simulate(SimulateParams::new(signals).stop_loss(Some(0.04)));
+/// assert_eq!(stopped.total_closed_trades, 1);
+/// ```
+///
+/// A trade closing at break-even closes the position like any other, so the next crossover can enter again.
+/// It counts towards `total_closed_trades`, but neither as a winning nor as a losing trade:
+/// ```
+/// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{simulate, SimulateParams} };
+///
+/// let tick = |time_open:u64, signal_in:f64, price:f64| TriggerSignal { signal_in, signal_out:9.0, time_open, time_close:time_open + 1, price_open:price, price_high:price, price_low:price, price_close:price, volume:1000.0, };
+/// // Entered and exited at 100 without fees, then entered at 100 again and exited at 110
+/// let signals = vec![tick(0, 10.0, 100.0), tick(2, 8.0, 100.0), tick(4, 10.0, 100.0), tick(6, 8.0, 100.0), tick(8, 8.0, 110.0)];
+///
+/// let pnl = simulate(SimulateParams::new(signals));
+/// assert_eq!(pnl.net_profit, 100.0);
+/// assert_eq!(pnl.total_closed_trades, 2);
+/// assert_eq!((pnl.num_winning_trades, pnl.num_losing_trades), (1, 0));
+/// ```
+///
+/// All amounts are tracked as [`Decimal`] and truncated to the wallet and exchange precision on every trade.
+/// See [`simulate_fast`] for a faster, less precise alternative.
 pub fn simulate(sim_params: SimulateParams) -> PnL {
-    let mut pnl = PnL {
-        net_profit      : 0.0,
-        gross_profit    : 0.0,
-        gross_loss      : 0.0,
-        buy_and_hold_return : 0.0,
-        profit_factor   : 0.0,
-        commission_paid : None,
-        total_closed_trades : 0,
-        num_winning_trades  : 0,
-        num_losing_trades   : 0,
-        percent_profitable  : 0.0,
-        avg_winning_trade   : 0.0,
-        avg_losing_trade    : 0.0,
-        ratio_avg_win_loss  : 0.0,
-        largest_winning_trade:   0.0,
-        largest_losing_trade    : 0.0,
-        avg_ticks_in_winning_trades : 0.0,
-        avg_ticks_in_losing_trades  : 0.0,
-    };
+    simulate_with::<Decimal>(&sim_params, false, &[]).pnl
+}
 
-    let asset_trade_scale = sim_params.get_asset_trade_scale();
-    let funds_trade_scale = sim_params.get_funds_trade_scale();
-
-    let exchange_fee : Option<Decimal> = sim_params.exchange_fee.map(|v| Decimal::from_f64(v).unwrap());
-    let mut funds = Decimal::from_f64(sim_params.initial_capital).unwrap();
-
-    pnl.buy_and_hold_return = buy_and_hold_return(
-        &funds,
-        &exchange_fee,
-        &Decimal::from_f64(sim_params.signals.get(0).unwrap().price_open).unwrap(),
-        &Decimal::from_f64(sim_params.signals.last().unwrap().price_close).unwrap(),
-        &sim_params.asset_scale,
-        &sim_params.funds_scale,
-        &funds_trade_scale,
-        &asset_trade_scale,
-    );
+/// Carries out the same simulation as [`simulate`], additionally returning every closed [`Trade`] and the equity curve.
+///
+/// Meant for auditing a single configuration, e.g. reconciling it against the trades listed by a charting platform.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::exchange::chart_data::klines::Intervals;
+/// use oscillatorsetups::pnl_simulator::{ models::{Direction, ExitReason, TriggerSignal}, pnl::{backtest, SimulateParams} };
+///
+/// let tick = |time_open:u64, signal_in:f64, price:f64| TriggerSignal { signal_in, signal_out:9.0, time_open, time_close:time_open + 1, price_open:price, price_high:price, price_low:price, price_close:price, volume:1000.0, };
+/// // Exited on the bearish crossover, then re-entered and still open at the last tick
+/// let signals = vec![tick(0, 10.0, 100.0), tick(2, 8.0, 100.0), tick(4, 10.0, 110.0), tick(6, 10.0, 110.0), tick(8, 10.0, 121.0)];
+///
+/// let result = backtest(SimulateParams::new(signals));
+/// let trade = &result.trades[0];
+///
+/// assert_eq!(result.trades.len(), 2);
+/// assert_eq!(trade.direction, Direction::Long);
+/// assert_eq!((trade.entry_time, trade.exit_time), (2, 4));
+/// assert_eq!((trade.entry_price, trade.exit_price), (100.0, 110.0));
+/// assert_eq!(trade.quantity, 10.0);
+/// assert_eq!(trade.ticks_held, 1);
+/// assert_eq!(trade.exit_reason, ExitReason::Signal);
+///
+/// // The position still open is closed at the open of the last tick
+/// let last = &result.trades[1];
+/// assert_eq!((last.entry_time, last.exit_time), (6, 8));
+/// assert_eq!(last.exit_reason, ExitReason::EndOfData);
+/// assert_eq!(trade.profit + last.profit, result.pnl.net_profit);
+///
+/// // Equity marked to market at the close of every tick
+/// assert_eq!(result.equity, vec![1000.0, 1000.0, 1100.0, 1100.0, 1210.0]);
+///
+/// // Annualized with the default daily interval, no tick lost equity
+/// assert!(result.pnl.sharpe_ratio > 0.0);
+/// assert_eq!(result.pnl.sortino_ratio, 0.0);
+/// assert_eq!(result.pnl.calmar_ratio, 0.0);
+///
+/// // An hour of 1m ticks gaining 10%, compounded over a year
+/// let signals = (0..60).map(|ix| tick(ix * 60, 10.0, 100.0 + ix as f64 / 6.0)).collect();
+/// let result = backtest(SimulateParams::new(signals).interval(Intervals::M1));
+///
+/// assert!(result.pnl.net_profit > 0.0);
+/// assert_eq!(result.pnl.cagr, f64::INFINITY);
+/// ```
+pub fn backtest(sim_params: SimulateParams) -> Backtest {
+    simulate_with::<Decimal>(&sim_params, true, &[])
+}
 
-    let mut position_open   : bool  = false;
-    let mut simulate_buy    : bool  = false;
-    let mut simulate_sell   : bool  = false;
+/// Carries out the simulation of [`backtest`] as consecutive windows, each ending with the tick of an index in `window_ends`.
+///
+/// A position still open at the end of a window is closed as at the end of the data, with [`ExitReason::WindowEnd`], and nothing
+/// staged within a window carries over to the next one. The ATR, the trade statistics of [`Sizing::Kelly`] and the traded volume of
+/// the fee tiers restart with every window as well. Only the funds carry over, so the windows enter and exit on the same ticks as
+/// when simulated one by one, staking amounts that follow the funds unless sized by [`Sizing::FixedQuote`].
+pub(crate) fn backtest_windows(sim_params: SimulateParams, window_ends: &[usize]) -> Backtest {
+    simulate_with::<Decimal>(&sim_params, true, window_ends)
+}
 
-    let mut asset_init_cost = dec!(0.0);
-    let mut assets:Decimal = dec!(0.0);
+/// Fast alternative of [`simulate`], carrying out the simulation in `f64`.
+///
+/// No truncation to `asset_scale`, `funds_scale`, `min_qty` or `min_price` is applied, so the results differ
+/// slightly from [`simulate`]. Meant for screening large parameter grids, where the best configurations
+/// can then be re-scored with [`simulate`].
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::{ models::TriggerSignal, pnl::{simulate, simulate_fast, SimulateParams} };
+///
+/// let tick = |time_open:u64, signal_in:f64, price:f64| TriggerSignal { signal_in, signal_out:9.0, time_open, time_close:time_open + 1, price_open:price, price_high:price, price_low:price, price_close:price, volume:1000.0, };
+/// // 1000 buys 32.5732... at 30.7, sold at 33
+/// let signals = || vec![tick(0, 10.0, 30.7), tick(1, 8.0, 30.7), tick(2, 8.0, 33.0)];
+///
+/// // The quantity is truncated to the 2 decimals of the wallet, only 32.57 is bought
+/// let precise = simulate(SimulateParams::new(signals()).asset_scale(2));
+/// let fast = simulate_fast(SimulateParams::new(signals()).asset_scale(2));
+///
+/// assert_eq!(precise.total_closed_trades, fast.total_closed_trades);
+/// assert_eq!(precise.net_profit, 74.911);
+/// assert!((fast.net_profit - 1000.0 / 30.7 * 2.3).abs() < 1e-9);
+/// ```
+pub fn simulate_fast(sim_params: SimulateParams) -> PnL {
+    simulate_with::<f64>(&sim_params, false, &[]).pnl
+}
 
-    let mut commission_paid = dec!(0.0);
+/// Carries out the simulation of [`simulate`] in the numeric type `N`, collecting the trades if `trade_log` is set.
+/// The ticks are split into windows ending with the sorted indexes of `window_ends`, see [`backtest_windows`].
+fn simulate_with<N: Amount>(sim_params: &SimulateParams, trade_log: bool, window_ends: &[usize]) -> Backtest {
+    let mut pnl = PnL::default();
 
-    let mut tik_at_purchase:u16 = 0;
-    let mut gross_profit = dec!(0.0);
+    // Nothing to simulate, e.g. oscillator lengths exceeding the available data
+    if sim_params.signals.is_empty() { return Backtest::default(); }
 
-    let mut winning_trades:Vec<Decimal> = vec![];
+    let mut account = Account::<N>::new(sim_params, trade_log);
+    let exchange_fee = account.fee_rate(false, 0);
 
-    let mut winning_ticks:Vec<u16> = vec![];
-    let mut loosing_ticks:Vec<u16> = vec![];
+    let sim_stop_at = sim_params.signals.len() -1;
 
-    let mut gross_loss = dec!(0.0);
-    let mut losing_trades:Vec<Decimal> = vec![];
+    let stake = sim_params.sizing.initial_stake(sim_params.initial_capital);
+    let funds = N::from_f64_value(stake);
+    // The benchmark is filled like the strategy, paying the slippage and spread of its entry and exit
+    let short = sim_params.position_mode == PositionMode::ShortOnly;
+    let price_open = sim_params.signals[0].price_open;
+    let qty = stake / price_open;
+    let price_first = N::from_f64_value(account.fill_price(!short, price_open, qty, 0));
+    let price_last = N::from_f64_value(account.fill_price(short, sim_params.signals[sim_stop_at].price_close, qty, sim_stop_at));
+
+    pnl.buy_and_hold_return = if short {
+        sell_and_hold_return(
+            &funds,
+            &exchange_fee,
+            &price_first,
+            &price_last,
+            &sim_params.asset_scale,
+            &sim_params.funds_scale,
+            &account.funds_trade_scale,
+        )
+    } else {
+        buy_and_hold_return(
+            &funds,
+            &exchange_fee,
+            &price_first,
+            &price_last,
+            &sim_params.asset_scale,
+            &sim_params.funds_scale,
+            &account.funds_trade_scale,
+            &account.asset_trade_scale,
+            sim_params.fee_currency != FeeCurrency::Native || sim_params.futures.is_some(),
+        )
+    };
 
-    let zero_val = dec!(0.0);
-    let min_funds = dec!(10.0);
+    let atr_line = |length: u16| {
+        let price_data:Vec<Hlc> = sim_params.signals
+            .iter()
+            .map(|tick| Hlc::new(tick.price_high, tick.price_low, tick.price_close))
+            .collect();
 
-    let sim_stop_at = sim_params.signals.len() -1;
+        // Started afresh with every window, as if simulated on its own
+        let mut line = Vec::with_capacity(price_data.len());
+        for end in window_ends.iter().map(|&end| end + 1).chain([price_data.len()]) {
+            if end > line.len() { line.extend(atr_for_ticks(&price_data[line.len()..end], length)); }
+        }
+        line
+    };
+    let atr = sim_params.atr_stop.map(|atr_stop| atr_line(atr_stop.length));
+    let sizing_atr = match sim_params.sizing {
+        Sizing::VolatilityTarget { atr_length, .. } => Some(atr_line(atr_length)),
+        _ => None,
+    };
+
+    let mut simulate_entry  : Option<Direction> = None;
+    let mut simulate_exit   : bool  = false;
+
+    let mut limit_order     : Option<LimitOrder> = None;
+    // Index of the next funding rate of the futures to pay
+    let mut next_funding    : usize = 0;
+
+    // Side of the last position closed by a protective exit, or of an expired limit order, not re-entered until the signals turn
+    let mut stopped_out     : Option<Direction> = None;
+    let mut exit_levels     : Option<ExitLevels> = None;
+
+    let min_funds = N::from_f64_value(10.0);
+    // Set once the funds left after a trade fall below `min_funds`, ending the simulation with that tick
+    let mut depleted        : bool  = false;
+
+    let mut equity = EquityCurve::new(sim_params.initial_capital, trade_log);
 
     for (indx,tick) in sim_params.signals.iter().enumerate() {
-        if simulate_buy {
-            let purchase = stage_purchase(
-                &funds,
-                &Decimal::from_f64(tick.price_open).unwrap(),
-                &exchange_fee,
-                &sim_params.asset_scale,
-                &sim_params.funds_scale,
-                &funds_trade_scale,
-            );
-
-            asset_init_cost     = purchase.total_fee.map_or(purchase.cost_before_fee, |fee| purchase.cost_before_fee + fee);
-            funds   -= asset_init_cost;
-            assets  += purchase.asset_qty;
-
-            if let Some(fee) = purchase.total_fee { commission_paid += fee; }
-
-            position_open   = true;
-            simulate_buy    = false;
-
-            tik_at_purchase = indx as u16;
+        let mut in_market = false;
+        // Whether a limit entry was filled within the range of the tick rather than at its open
+        let mut entered_within = false;
+        let window_end = indx == sim_stop_at || window_ends.binary_search(&indx).is_ok();
+
+        for step in sim_params.execution.steps() {
+            // Direction, fill price and time, tick staged on and whether the entry rested on the book
+            let entry = match step {
+                Step::Fill => {
+                    let price = sim_params.execution.price(tick);
+                    let time = sim_params.execution.time(tick);
+
+                    // A protective exit earlier within the tick may have closed the position already
+                    let exit_staged = std::mem::take(&mut simulate_exit);
+
+                    if account.position.is_some() && (exit_staged || window_end) {
+                        let reason = if exit_staged {
+                            ExitReason::Signal
+                        } else if indx == sim_stop_at {
+                            ExitReason::EndOfData
+                        } else {
+                            ExitReason::WindowEnd
+                        };
+                        account.exit(price, indx, time, reason);
+
+                        depleted = account.funds < min_funds;
+                    }
+
+                    simulate_entry.take().map(|direction| {
+                        let staged_at = if sim_params.execution == Execution::SignalClose { indx } else { indx - 1 };
+                        (direction, price, time, staged_at, false)
+                    })
+                },
+                Step::Limit => {
+                    // Until the position reversed is closed, at the close of the tick with `NextClose` and `NextVwap`
+                    limit_order.filter(|order| indx > order.placed_at && account.position.is_none()).and_then(|order| {
+                        let fill = order.fill(tick);
+                        if fill.is_some() || order.expired(indx) {
+                            limit_order = None;
+                            if fill.is_none() { stopped_out = Some(order.direction); }
+                        }
+                        fill.map(|price| (order.direction, price, tick.time_open, order.placed_at, true))
+                    })
+                },
+                Step::Hold => {
+                    in_market = account.position.is_some();
+
+                    if let Some(futures) = &sim_params.futures {
+                        while let Some(funding) = futures.funding.get(next_funding).filter(|funding| funding.time <= tick.time_close) {
+                            if funding.time >= tick.time_open {
+                                account.fund(funding.rate, tick.price_open);
+                                if let Some(levels) = exit_levels.as_mut() { levels.liquidation = account.liquidation_price(); }
+                            }
+                            next_funding += 1;
+                        }
+                    }
+
+                    // The range of the tick preceded a limit entry filled within it
+                    if let (Some(direction), Some(levels)) = (account.position, exit_levels.as_mut().filter(|_| !entered_within)) {
+                        if let Some((price, reason)) = levels.hit(tick) {
+                            account.exit(price, indx, tick.time_open, reason);
+                            stopped_out = Some(direction);
+
+                            depleted = account.funds < min_funds;
+                        }
+                    }
+                    None
+                },
+                Step::Signal => {
+                    let signal = if tick.signal_in > tick.signal_out {
+                        Some(Direction::Long)
+                    } else if tick.signal_in < tick.signal_out {
+                        Some(Direction::Short)
+                    } else {
+                        None
+                    };
+
+                    if signal.is_some() && stopped_out != signal { stopped_out = None; }
+
+                    if let Some(direction) = signal {
+                        if account.position.is_some_and(|open| open != direction) { simulate_exit = true; }
+                        if limit_order.is_some_and(|order| order.direction != direction) { limit_order = None; }
+
+                        if account.position != Some(direction) && stopped_out.is_none() && sim_params.position_mode.allows(direction) {
+                            match sim_params.entry_order {
+                                EntryOrder::Market => simulate_entry = Some(direction),
+                                EntryOrder::Limit { offset, bars } => if limit_order.is_none() {
+                                    limit_order = Some(LimitOrder::new(direction, tick.price_close, offset, indx, bars));
+                                },
+                            }
+                        }
+                    }
+                    None
+                },
+            };
+
+            // Entries staged on the last tick of a window could not be closed anymore
+            if let Some((direction, price, time, staged_at, maker)) = entry.filter(|_| !window_end && !depleted) {
+                // The ATR of the tick the entry was staged on, the latest one known when filling
+                let sizing_atr_value = sizing_atr.as_ref().and_then(|atr| atr[staged_at]);
+
+                if let Some(stake) = account.stake(price, sizing_atr_value) {
+                    account.enter(direction, price, indx, time, stake, maker);
+                    entered_within = maker && price != tick.price_open;
+
+                    let atr_value = atr.as_ref().and_then(|atr| atr[staged_at]);
+                    exit_levels = Some(ExitLevels::new(sim_params, direction, price, atr_value, account.liquidation_price()));
+                }
+            }
         }
 
-        else if simulate_sell || (indx == sim_stop_at && position_open)  {
-            let tik_price_open = Decimal::from_f64(tick.price_open).unwrap();
-            let sell = stage_sale(
-                &assets,
-                &tik_price_open,
-                &exchange_fee,
-                &sim_params.asset_scale,
-                &sim_params.funds_scale,
-                &asset_trade_scale,
-            );
-            funds   += sell.sale_before_fee;
-            assets  -= sell.assets_sold;
-
-            let mut trade_profit = sell.sale_before_fee - asset_init_cost;
-
-            if let Some(fee) = sell.fee_asset_total {
-                let commission_cost = fee * tik_price_open;
-
-                commission_paid += commission_cost;
-                assets  -= fee;
-                trade_profit -= commission_cost;
-            }
+        equity.record(indx, account.equity(tick.price_close), in_market);
+        if depleted { break; }
+
+        // The next window starts flat, as if simulated on its own
+        if window_end {
+            simulate_entry = None;
+            simulate_exit = false;
+            limit_order = None;
+            stopped_out = None;
+            account.start_window();
+        }
+    }
 
-            pnl.total_closed_trades += 1;
+    account.summarize(&mut pnl);
+    equity.summarize(sim_params.signals.len(), sim_params.interval, &mut pnl);
 
-            #[allow(clippy::comparison_chain)]
-            if trade_profit > zero_val {
-                gross_profit += trade_profit;
-                pnl.num_winning_trades +=1;
-                winning_trades.push(trade_profit);
-                winning_ticks.push(indx as u16 - tik_at_purchase);
-            }
-            else if trade_profit < zero_val {
-                gross_loss += trade_profit;
-                pnl.num_losing_trades +=1;
-                losing_trades.push(trade_profit);
-                loosing_ticks.push(indx as u16 - tik_at_purchase);
-            }
+    Backtest { pnl, trades: account.trades.unwrap_or_default(), equity: equity.curve.unwrap_or_default() }
+}
 
-            if trade_profit != zero_val {
-                position_open = false;
-                simulate_sell = false;
+/// The period the traded volume of the [`FeeTier`]s is summed over, 30 days in milliseconds.
+const FEE_TIER_WINDOW: u64 = 30 * 86_400_000;
+
+/// Funds, holdings and closed trades of a simulation, tracked in the numeric type `N`.
+struct Account<'p, N> {
+    sim_params  : &'p SimulateParams,
+
+    asset_trade_scale   : Option<u32>,
+    funds_trade_scale   : Option<u32>,
+
+    funds       : N,
+    assets      : N,
+    borrowed    : N,
+
+    /// `funds` and the net asset holdings as `f64`, refreshed on every trade to mark the equity to market
+    cash            : f64,
+    holding         : f64,
+
+    /// Time and quote value of the fills of the last 30 days and their total, only tracked with `fee_tiers`
+    fills_window    : VecDeque<(u64, f64)>,
+    window_volume   : f64,
+
+    /// Side of the open position, if any
+    position        : Option<Direction>,
+    /// Cost of a long position including the fee, net proceeds of a short sale after the fee
+    position_value  : N,
+    tik_at_entry    : usize,
+    time_at_entry   : u64,
+    price_at_entry  : f64,
+    qty_at_entry    : N,
+    fee_at_entry    : N,
+    /// Initial margin of a futures position, and the funding paid while it is held
+    margin_at_entry : f64,
+    funding_at_entry: N,
+
+    commission_paid : N,
+    funding_paid    : N,
+    /// Counts of the closed, winning and losing trades, and sums of the winning and the absolute losing trades as `f64`,
+    /// for the Kelly sizing. Restarted with every window, see [`Account::start_window`]
+    kelly_closed    : usize,
+    kelly_wins      : usize,
+    kelly_losses    : usize,
+    sum_wins        : f64,
+    sum_losses      : f64,
+    winning_trades  : Vec<N>,
+    losing_trades   : Vec<N>,
+    winning_ticks   : Vec<usize>,
+    loosing_ticks   : Vec<usize>,
+    total_closed_trades : i32,
+    /// The closed trades, only collected for [`backtest`]
+    trades          : Option<Vec<Trade>>,
+}
+
+impl<'p, N: Amount> Account<'p, N> {
+    fn new(sim_params: &'p SimulateParams, trade_log: bool) -> Self {
+        Account { sim_params,
+            asset_trade_scale   : sim_params.get_asset_trade_scale(),
+            funds_trade_scale   : sim_params.get_funds_trade_scale(),
+
+            funds       : N::from_f64_value(sim_params.initial_capital),
+            assets      : N::ZERO,
+            borrowed    : N::ZERO,
+
+            cash            : sim_params.initial_capital,
+            holding         : 0.0,
+
+            fills_window    : VecDeque::new(),
+            window_volume   : 0.0,
+
+            position        : None,
+            position_value  : N::ZERO,
+            tik_at_entry    : 0,
+            time_at_entry   : 0,
+            price_at_entry  : 0.0,
+            qty_at_entry    : N::ZERO,
+            fee_at_entry    : N::ZERO,
+            margin_at_entry : 0.0,
+            funding_at_entry: N::ZERO,
+
+            commission_paid : N::ZERO,
+            funding_paid    : N::ZERO,
+            kelly_closed    : 0,
+            kelly_wins      : 0,
+            kelly_losses    : 0,
+            sum_wins        : 0.0,
+            sum_losses      : 0.0,
+            winning_trades  : vec![],
+            losing_trades   : vec![],
+            winning_ticks   : vec![],
+            loosing_ticks   : vec![],
+            total_closed_trades : 0,
+            trades          : trade_log.then(Vec::new),
+        }
+    }
+
+    /// Returns the stake of an entry at `price` by the [`Sizing`], capped by the funds.
+    /// `atr` is the latest ATR for [`Sizing::VolatilityTarget`]. `None` if no position should be taken.
+    fn stake(&self, price: f64, atr: Option<f64>) -> Option<N> {
+        let equity = self.equity(price);
+
+        let stake = match self.sim_params.sizing {
+            Sizing::AllFunds => return Some(self.funds),
+            Sizing::FixedQuote(stake) => stake,
+            Sizing::EquityFraction(fraction) => equity * fraction,
+            Sizing::VolatilityTarget { risk, .. } => equity * risk / atr.filter(|&atr| atr > 0.0)? * price,
+            Sizing::Kelly { fraction, min_trades, min_criterion } => {
+                let closed = self.kelly_closed;
+                if closed < min_trades.max(1) {
+                    equity * fraction
+                } else {
+                    let win_rate = self.kelly_wins as f64 / closed as f64;
+                    let criterion = if self.kelly_losses == 0 || self.kelly_wins == 0 {
+                        win_rate
+                    } else {
+                        let avg_win = self.sum_wins / self.kelly_wins as f64;
+                        let avg_loss = self.sum_losses / self.kelly_losses as f64;
+                        win_rate - (1.0 - win_rate) / (avg_win / avg_loss)
+                    };
+                    equity * fraction * criterion.min(1.0).max(min_criterion)
+                }
+            },
+        };
+
+        // These size the position itself, of which a leveraged position only takes a fraction as its margin
+        let stake = match (self.sim_params.sizing, &self.sim_params.futures) {
+            (Sizing::FixedQuote(_) | Sizing::VolatilityTarget { .. }, Some(futures)) => stake / futures.leverage,
+            _ => stake,
+        };
+        if stake.is_nan() || stake <= 0.0 { return None; }
+
+        let stake = N::from_f64_value(stake);
+        Some(if stake < self.funds { stake } else { self.funds })
+    }
+
+    /// Opens a position in `direction` at `price` and `time` within tick `indx`, sized with `stake` out of the funds,
+    /// times the leverage of [`Futures`]. A `maker` entry fills at its limit `price` and is charged the maker rate.
+    fn enter(&mut self, direction: Direction, price: f64, indx: usize, time: u64, stake: N, maker: bool) {
+        let size = self.sim_params.futures.as_ref().map_or(stake, |futures| stake * N::from_f64_value(futures.leverage));
+        let price = if maker { price } else { self.fill_price(direction == Direction::Long, price, size.to_f64_value() / price, indx) };
+        let purchase = stage_purchase(
+            &size,
+            &N::from_f64_value(price),
+            &self.fee_rate(maker, indx),
+            &self.sim_params.asset_scale,
+            &self.sim_params.funds_scale,
+            &self.funds_trade_scale,
+        );
+
+        if let Some(fee) = purchase.total_fee { self.commission_paid += fee; }
+        self.record_fill(indx, purchase.cost_before_fee);
+
+        match direction {
+            Direction::Long => {
+                self.position_value = purchase.total_fee.map_or(purchase.cost_before_fee, |fee| purchase.cost_before_fee + fee);
+                self.funds  -= self.position_value;
+                self.assets += purchase.asset_qty;
+            },
+            // The short sale is sized like a purchase, so the funds cover the position and its fee
+            Direction::Short => {
+                self.position_value = purchase.total_fee.map_or(purchase.cost_before_fee, |fee| purchase.cost_before_fee - fee);
+                self.funds  += self.position_value;
+                self.borrowed = purchase.asset_qty;
+            },
+        }
+
+        self.mark();
+        self.position = Some(direction);
+        self.tik_at_entry = indx;
+        self.time_at_entry = time;
+        self.price_at_entry = price;
+        self.qty_at_entry = purchase.asset_qty;
+        self.fee_at_entry = purchase.total_fee.unwrap_or(N::ZERO);
+        self.margin_at_entry = stake.to_f64_value();
+        self.funding_at_entry = N::ZERO;
+    }
+
+    /// Closes the open position at `price` and `time` within tick `indx`, and records the trade.
+    fn exit(&mut self, price_exit: f64, indx: usize, time: u64, reason: ExitReason) {
+        let Some(direction) = self.position.take() else { return; };
+        let qty = match direction { Direction::Long => self.assets, Direction::Short => self.borrowed };
+        // The take-profit rests on the book and fills at its limit, every other exit takes liquidity
+        let maker = reason == ExitReason::TakeProfit;
+        let price_exit = if maker { price_exit } else { self.fill_price(direction == Direction::Short, price_exit, qty.to_f64_value(), indx) };
+        let price = N::from_f64_value(price_exit);
+        let fee_rate = self.fee_rate(maker, indx);
+        let mut fee_at_exit = N::ZERO;
+
+        let trade_profit = match direction {
+            Direction::Long => {
+                let fee_in_quote = self.sim_params.fee_currency != FeeCurrency::Native || self.sim_params.futures.is_some();
+                let sell = stage_sale(
+                    &self.assets,
+                    &price,
+                    if fee_in_quote { &None } else { &fee_rate },
+                    &self.sim_params.asset_scale,
+                    &self.sim_params.funds_scale,
+                    &self.asset_trade_scale,
+                );
+                self.funds  += sell.sale_before_fee;
+                self.assets -= sell.assets_sold;
+                self.record_fill(indx, sell.sale_before_fee);
+
+                if fee_in_quote {
+                    // The fee is charged in funds on top of the sale
+                    if let Some(rate) = fee_rate {
+                        let fee = sell.sale_before_fee * rate;
+
+                        self.commission_paid += fee;
+                        self.funds -= fee;
+                        fee_at_exit = fee;
+                    }
+                    sell.sale_before_fee - fee_at_exit - self.position_value
+                } else {
+                    // The fee is withheld from the assets sold, the proceeds are already net of it
+                    if let Some(fee) = sell.fee_asset_total {
+                        let commission_cost = fee * price;
+
+                        self.commission_paid += commission_cost;
+                        self.assets -= fee;
+                        fee_at_exit = commission_cost;
+                    }
+                    sell.sale_before_fee - self.position_value
+                }
+            },
+            Direction::Short => {
+                let cover = stage_cover(
+                    &self.borrowed,
+                    &price,
+                    &fee_rate,
+                    &self.sim_params.funds_scale,
+                );
+                let cover_cost = cover.total_fee.map_or(cover.cost_before_fee, |fee| cover.cost_before_fee + fee);
+                self.record_fill(indx, cover.cost_before_fee);
+
+                self.funds  -= cover_cost;
+                self.borrowed = N::ZERO;
+
+                if let Some(fee) = cover.total_fee {
+                    self.commission_paid += fee;
+                    fee_at_exit = fee;
+                }
+
+                self.position_value - cover_cost
+            },
+        };
+
+        let trade_profit = trade_profit - self.funding_at_entry;
+
+        self.mark();
+        self.total_closed_trades += 1;
+
+        if let Some(trades) = self.trades.as_mut() {
+            trades.push(Trade { direction,
+                entry_time  : self.time_at_entry,
+                exit_time   : time,
+                entry_price : self.price_at_entry,
+                exit_price  : price_exit,
+                quantity    : self.qty_at_entry.to_f64_value(),
+                fees        : (self.fee_at_entry + fee_at_exit).to_f64_value(),
+                funding     : self.funding_at_entry.to_f64_value(),
+                profit      : trade_profit.to_f64_value(),
+                ticks_held  : indx - self.tik_at_entry,
+                exit_reason : reason,
+            });
+        }
+
+        self.kelly_closed += 1;
+
+        #[allow(clippy::comparison_chain)]
+        if trade_profit > N::ZERO {
+            self.kelly_wins += 1;
+            self.sum_wins += trade_profit.to_f64_value();
+            self.winning_trades.push(trade_profit);
+            self.winning_ticks.push(indx - self.tik_at_entry);
+        }
+        else if trade_profit < N::ZERO {
+            self.kelly_losses += 1;
+            self.sum_losses -= trade_profit.to_f64_value();
+            self.losing_trades.push(trade_profit);
+            self.loosing_ticks.push(indx - self.tik_at_entry);
+        }
+    }
+
+    /// Returns the fee rate of a `maker` (or taker) fill within tick `indx`, by the [`FeeTier`] reached
+    /// and after the discount of [`FeeCurrency::ThirdAsset`].
+    fn fee_rate(&mut self, maker: bool, indx: usize) -> Option<N> {
+        let sim_params = self.sim_params;
+
+        let tier_rate = if sim_params.fee_tiers.is_empty() { None } else {
+            let now = sim_params.signals[indx].time_open;
+            while let Some(&(time, notional)) = self.fills_window.front() {
+                if time + FEE_TIER_WINDOW > now { break; }
+                self.window_volume -= notional;
+                self.fills_window.pop_front();
             }
 
-            if funds < min_funds { break; };
+            sim_params.fee_tiers.iter()
+                .filter(|tier| tier.min_volume <= self.window_volume)
+                .max_by(|a, b| a.min_volume.total_cmp(&b.min_volume))
+                .map(|tier| if maker { tier.maker } else { tier.taker })
+        };
+
+        let rate = tier_rate.or(if maker { sim_params.maker_fee.or(sim_params.exchange_fee) } else { sim_params.exchange_fee })?;
+
+        Some(N::from_f64_value(match sim_params.fee_currency {
+            FeeCurrency::ThirdAsset { discount } => rate * (1.0 - discount),
+            _ => rate,
+        }))
+    }
+
+    /// Forgets the trades and fills of the previous windows, so the Kelly sizing and the fee tiers of the next window
+    /// only depend on its own trades, as if it was simulated on its own. The funds carry over.
+    fn start_window(&mut self) {
+        self.kelly_closed = 0;
+        self.kelly_wins = 0;
+        self.kelly_losses = 0;
+        self.sum_wins = 0.0;
+        self.sum_losses = 0.0;
+
+        self.fills_window.clear();
+        self.window_volume = 0.0;
+    }
+
+    /// Adds a fill of `notional` quote value within tick `indx` to the traded volume of the fee tiers.
+    fn record_fill(&mut self, indx: usize, notional: N) {
+        if self.sim_params.fee_tiers.is_empty() { return; }
+
+        let notional = notional.to_f64_value();
+        self.fills_window.push_back((self.sim_params.signals[indx].time_open, notional));
+        self.window_volume += notional;
+    }
+
+    /// Returns the price a `buy` (or sell) of `qty` quoted at `price` fills at within tick `indx`,
+    /// after the [`Slippage`] and half of the spread.
+    fn fill_price(&self, buy: bool, price: f64, qty: f64, indx: usize) -> f64 {
+        let tick = &self.sim_params.signals[indx];
+        let offset = self.sim_params.slippage.offset(price, qty, tick)
+            + self.sim_params.spread.map_or(0.0, |spread| price * spread / 2.0);
+
+        if offset == 0.0 { price }
+        else if buy { price + offset }
+        else { (price - offset).max(0.0) }
+    }
+
+    /// Returns the price the open futures position is liquidated at, once its isolated margin, less the funding paid
+    /// out of it, falls to the maintenance margin. `None` for spot, or if the position is not leveraged.
+    fn liquidation_price(&self) -> Option<f64> {
+        let futures = self.sim_params.futures.as_ref()?;
+        let qty = self.qty_at_entry.to_f64_value();
+        let notional = qty * self.price_at_entry;
+        let margin = self.margin_at_entry - self.funding_at_entry.to_f64_value();
+
+        let price = match self.position? {
+            Direction::Long     => (notional - margin) / (qty * (1.0 - futures.maintenance_margin)),
+            Direction::Short    => (notional + margin) / (qty * (1.0 + futures.maintenance_margin)),
+        };
+        (price > 0.0 && price.is_finite()).then_some(price)
+    }
+
+    /// Pays the funding `rate` of the notional of the open position at `price`, received if negative for the side held.
+    fn fund(&mut self, rate: f64, price: f64) {
+        if self.position.is_none() { return; }
+
+        let payment = N::from_f64_value(self.holding * price * rate).trunc_with_scale(self.sim_params.funds_scale);
+        self.funds -= payment;
+        self.funding_at_entry += payment;
+        self.funding_paid += payment;
+        self.mark();
+    }
+
+    /// Refreshes the `f64` copies of the funds and holdings after a trade.
+    fn mark(&mut self) {
+        self.cash = self.funds.to_f64_value();
+        self.holding = (self.assets - self.borrowed).to_f64_value();
+    }
+
+    /// Returns the mark-to-market equity at `price`: the funds plus the value of the assets held, less the assets borrowed.
+    fn equity(&self, price: f64) -> f64 {
+        self.cash + self.holding * price
+    }
+
+    /// Fills the trade statistics of `pnl` from the closed trades.
+    fn summarize(&self, pnl: &mut PnL) {
+        let gross_profit = self.winning_trades.iter().fold(N::ZERO, |a, &b| a + b);
+        let gross_loss = self.losing_trades.iter().fold(N::ZERO, |a, &b| a + b);
+
+        pnl.net_profit = (gross_profit + gross_loss).to_f64_value();
+        pnl.commission_paid = Some(self.commission_paid.to_f64_value());
+        pnl.funding_paid = self.funding_paid.to_f64_value();
+        pnl.gross_profit = gross_profit.to_f64_value();
+        pnl.gross_loss = gross_loss.to_f64_value();
+
+        pnl.total_closed_trades = self.total_closed_trades;
+        pnl.num_winning_trades = self.winning_trades.len() as i32;
+        pnl.num_losing_trades = self.losing_trades.len() as i32;
+
+        if pnl.total_closed_trades > 0 {
+            let percentage = N::from_f64_value(pnl.num_winning_trades as f64) / N::from_f64_value(pnl.total_closed_trades as f64) * N::from_f64_value(100.0);
+            pnl.percent_profitable = percentage.round_dp(2).to_f64_value();
+        }
+
+        pnl.avg_winning_trade = array_of_decimal_avg(&self.winning_trades);
+        pnl.avg_losing_trade = array_of_decimal_avg(&self.losing_trades);
+
+        if pnl.avg_losing_trade != 0.0 {
+            let avg_winning_trade = N::from_f64_value(pnl.avg_winning_trade);
+            let avg_losing_trade = N::from_f64_value(pnl.avg_losing_trade).abs();
+            pnl.ratio_avg_win_loss = (avg_winning_trade / avg_losing_trade).round_dp(3).to_f64_value();
+        }
+
+        if let Some(&max) = self.winning_trades.iter().max_by(|a, b| a.partial_cmp(b).unwrap()) {
+            pnl.largest_winning_trade = max.round_dp(2).to_f64_value();
         }
 
-        if tick.signal_in > tick.signal_out && !position_open {
-            simulate_buy = true;
-        } else if tick.signal_in < tick.signal_out && position_open {
-            simulate_sell = true;
+        if let Some(&min) = self.losing_trades.iter().min_by(|a, b| a.partial_cmp(b).unwrap()) {
+            pnl.largest_losing_trade = min.round_dp(2).to_f64_value();
         }
+
+        let sum_tik_wins:usize = self.winning_ticks.iter().sum();
+        pnl.avg_ticks_in_winning_trades = sum_tik_wins as f64 / (self.winning_ticks.len() as f64);
+
+        let sum_tik_losses:usize = self.loosing_ticks.iter().sum();
+        pnl.avg_ticks_in_losing_trades = sum_tik_losses as f64 / (self.loosing_ticks.len() as f64);
+
+        pnl.profit_factor = profit_factor(&self.winning_trades, &self.losing_trades)
+            .unwrap_or(0.0);
     }
+}
+
+/// Tracks the equity of a simulation tick by tick, deriving its drawdown and exposure.
+struct EquityCurve {
+    initial_capital : f64,
+    /// Highest equity so far, starting with the initial capital
+    peak        : f64,
+    tik_at_peak : usize,
+    last        : f64,
+    recorded    : usize,
+
+    max_drawdown            : f64,
+    max_drawdown_percent    : f64,
+    max_drawdown_duration   : usize,
+    ticks_in_market         : usize,
+
+    /// Sum of the per-tick returns, of their squares, and of the squares of the negative ones
+    sum_returns             : f64,
+    sum_squared_returns     : f64,
+    sum_squared_downside    : f64,
+
+    /// The equity of every tick, only collected for [`backtest`]
+    curve       : Option<Vec<f64>>,
+}
 
-    pnl.net_profit = (gross_profit + gross_loss).to_f64().unwrap();
-    pnl.commission_paid = commission_paid.to_f64();
-    pnl.gross_profit = gross_profit.to_f64().unwrap();
-    pnl.gross_loss = gross_loss.to_f64().unwrap();
+impl EquityCurve {
+    fn new(initial_capital: f64, collect: bool) -> Self {
+        EquityCurve { initial_capital,
+            peak        : initial_capital,
+            tik_at_peak : 0,
+            last        : initial_capital,
+            recorded    : 0,
 
-    let percentage = Decimal::from_i32(pnl.num_winning_trades).unwrap() / Decimal::from_i32(pnl.total_closed_trades).unwrap() * dec!(100.0);
-    pnl.percent_profitable = percentage.round_dp(2).to_f64().unwrap();
+            max_drawdown            : 0.0,
+            max_drawdown_percent    : 0.0,
+            max_drawdown_duration   : 0,
+            ticks_in_market         : 0,
 
-    pnl.avg_winning_trade = array_of_decimal_avg(&winning_trades);
-    pnl.avg_losing_trade = array_of_decimal_avg(&losing_trades);
+            sum_returns             : 0.0,
+            sum_squared_returns     : 0.0,
+            sum_squared_downside    : 0.0,
 
-    if pnl.avg_losing_trade != 0.0 {
-        let avg_winning_trade = Decimal::from_f64(pnl.avg_winning_trade).unwrap();
-        let avg_losing_trade = Decimal::from_f64(pnl.avg_losing_trade).unwrap().abs();
-        pnl.ratio_avg_win_loss = (avg_winning_trade / avg_losing_trade).round_dp(3).to_f64().unwrap();
+            curve       : collect.then(Vec::new),
+        }
     }
 
-    if let Some(&max) = winning_trades.iter().max_by(|a, b| a.partial_cmp(b).unwrap()) {
-        pnl.largest_winning_trade = max.round_dp(2).to_f64().unwrap();
+    /// Records the `equity` at the close of tick `indx`, `in_market` if a position was held during the tick.
+    fn record(&mut self, indx: usize, equity: f64, in_market: bool) {
+        if equity >= self.peak {
+            self.peak = equity;
+            self.tik_at_peak = indx;
+        } else {
+            let drawdown = self.peak - equity;
+            self.max_drawdown = self.max_drawdown.max(drawdown);
+            self.max_drawdown_percent = self.max_drawdown_percent.max(drawdown / self.peak * 100.0);
+        }
+        self.max_drawdown_duration = self.max_drawdown_duration.max(indx - self.tik_at_peak);
+
+        let tick_return = if self.last != 0.0 { equity / self.last - 1.0 } else { 0.0 };
+        self.sum_returns += tick_return;
+        self.sum_squared_returns += tick_return * tick_return;
+        if tick_return < 0.0 { self.sum_squared_downside += tick_return * tick_return; }
+
+        if in_market { self.ticks_in_market += 1; }
+        if let Some(curve) = self.curve.as_mut() { curve.push(equity); }
+        self.last = equity;
+        self.recorded += 1;
     }
 
-    if let Some(&min) = losing_trades.iter().min_by(|a, b| a.partial_cmp(b).unwrap()) {
-        pnl.largest_losing_trade = min.round_dp(2).to_f64().unwrap();
+    /// Fills the drawdown, exposure and return statistics of `pnl`, over `ticks` ticks lasting `interval` each.
+    /// Ticks left out by a simulation ended early are recorded at the last equity.
+    ///
+    /// Returns are annualized over a year of 365 days, as crypto markets trade around the clock, with a risk-free rate of 0.
+    fn summarize(&mut self, ticks: usize, interval: Intervals, pnl: &mut PnL) {
+        for indx in self.recorded..ticks {
+            self.record(indx, self.last, false);
+        }
+
+        pnl.max_drawdown = self.max_drawdown.round_dp(2);
+        pnl.max_drawdown_percent = self.max_drawdown_percent.round_dp(2);
+        pnl.max_drawdown_duration = self.max_drawdown_duration;
+        pnl.exposure = (self.ticks_in_market as f64 / ticks as f64 * 100.0).round_dp(2);
+
+        let ticks_per_year = (365 * 86400) as f64 / interval.value() as f64;
+        let count = self.recorded as f64;
+        let mean = self.sum_returns / count;
+
+        if self.recorded > 1 {
+            let variance = (self.sum_squared_returns - count * mean * mean) / (count - 1.0);
+            if variance > 0.0 { pnl.sharpe_ratio = (mean / variance.sqrt() * ticks_per_year.sqrt()).round_dp(3); }
+        }
+
+        let downside_deviation = (self.sum_squared_downside / count).sqrt();
+        if downside_deviation > 0.0 { pnl.sortino_ratio = (mean / downside_deviation * ticks_per_year.sqrt()).round_dp(3); }
+
+        let growth = self.last / self.initial_capital;
+        pnl.cagr = if growth > 0.0 { (growth.powf(ticks_per_year / count) - 1.0) * 100.0 } else { -100.0 };
+
+        if self.max_drawdown_percent > 0.0 { pnl.calmar_ratio = (pnl.cagr / self.max_drawdown_percent).round_dp(3); }
+        pnl.cagr = pnl.cagr.round_dp(2);
     }
+}
 
-    let sum_tik_wins:u16 = winning_ticks.iter().sum();
-    pnl.avg_ticks_in_winning_trades = sum_tik_wins as f64 / (winning_ticks.len() as f64);
+/// Protective exit levels of an open position, see [`SimulateParams`].
+///
+/// Levels are checked intrabar against the high and low of each tick, starting with the tick the position is entered on
+/// if filled at its open.
+/// A tick opening beyond a level fills at its open. If both the stop and the target lie within the same tick,
+/// the stop is assumed to be hit first.
+struct ExitLevels {
+    direction   : Direction,
+    /// The tighter of the `stop_loss` and `atr_stop` levels
+    stop        : Option<(f64, ExitReason)>,
+    /// The liquidation price of a futures position, moved by the funding paid out of its margin
+    liquidation : Option<f64>,
+    target      : Option<f64>,
+    trailing    : Option<f64>,
+    /// Highest high of a long, lowest low of a short position, of the ticks before the current one
+    extreme     : f64,
+}
 
-    let sum_tik_losses:u16 = loosing_ticks.iter().sum();
-    pnl.avg_ticks_in_losing_trades = sum_tik_losses as f64 / (loosing_ticks.len() as f64);
+impl ExitLevels {
+    fn new(sim_params: &SimulateParams, direction: Direction, price_entry: f64, atr: Option<f64>, liquidation: Option<f64>) -> Self {
+        let side = match direction { Direction::Long => 1.0, Direction::Short => -1.0 };
+
+        let fixed_stop = sim_params.stop_loss
+            .map(|loss| (price_entry * (1.0 - side * loss), ExitReason::StopLoss));
+        let atr_stop = sim_params.atr_stop.zip(atr)
+            .map(|(atr_stop, atr)| (price_entry - side * atr_stop.multiple * atr, ExitReason::AtrStop));
+
+        let mut levels = ExitLevels { direction,
+            stop        : None,
+            liquidation,
+            target      : sim_params.take_profit.map(|profit| price_entry * (1.0 + side * profit)),
+            trailing    : sim_params.trailing_stop,
+            extreme     : price_entry,
+        };
+        levels.stop = levels.tighter(fixed_stop, atr_stop);
+        levels
+    }
 
-    pnl.profit_factor = profit_factor(&winning_trades, &losing_trades)
-        .unwrap_or(0.0);
+    /// Returns the tighter of two stop levels, the higher for a long and the lower for a short position.
+    fn tighter(&self, stop: Option<(f64, ExitReason)>, other: Option<(f64, ExitReason)>) -> Option<(f64, ExitReason)> {
+        match (stop, other) {
+            (Some(stop), Some(other)) => Some(match self.direction {
+                Direction::Long     => if other.0 > stop.0 { other } else { stop },
+                Direction::Short    => if other.0 < stop.0 { other } else { stop },
+            }),
+            (stop, other) => stop.or(other),
+        }
+    }
 
-    pnl
+    /// Returns the fill price and the level hit within `tick`, if any, and moves the trailing stop along.
+    fn hit(&mut self, tick: &TriggerSignal) -> Option<(f64, ExitReason)> {
+        let trailing_stop = self.trailing.map(|trailing| match self.direction {
+            Direction::Long     => (self.extreme * (1.0 - trailing), ExitReason::TrailingStop),
+            Direction::Short    => (self.extreme * (1.0 + trailing), ExitReason::TrailingStop),
+        });
+        let stop = self.tighter(self.stop, trailing_stop);
+        let stop = self.tighter(stop, self.liquidation.map(|price| (price, ExitReason::Liquidation)));
+
+        match self.direction {
+            Direction::Long => {
+                self.extreme = self.extreme.max(tick.price_high);
+
+                stop.filter(|&(stop, _)| tick.price_low <= stop).map(|(stop, reason)| (stop.min(tick.price_open), reason))
+                    .or_else(|| self.target.filter(|&target| tick.price_high >= target)
+                        .map(|target| (target.max(tick.price_open), ExitReason::TakeProfit)))
+            },
+            Direction::Short => {
+                self.extreme = self.extreme.min(tick.price_low);
+
+                stop.filter(|&(stop, _)| tick.price_high >= stop).map(|(stop, reason)| (stop.max(tick.price_open), reason))
+                    .or_else(|| self.target.filter(|&target| tick.price_low <= target)
+                        .map(|target| (target.min(tick.price_open), ExitReason::TakeProfit)))
+            },
+        }
+    }
+}
+
+/// A resting limit order entering a position, see [`EntryOrder::Limit`].
+#[derive(Clone, Copy)]
+struct LimitOrder {
+    direction   : Direction,
+    price       : f64,
+    /// The tick the order was placed at the close of
+    placed_at   : usize,
+    /// The last tick the order rests on
+    expires_at  : usize,
+}
+
+impl LimitOrder {
+    /// Places the order `offset` away from `price_close`, below it for a long and above it for a short entry.
+    fn new(direction: Direction, price_close: f64, offset: f64, placed_at: usize, bars: usize) -> Self {
+        let side = match direction { Direction::Long => 1.0, Direction::Short => -1.0 };
+
+        LimitOrder { direction,
+            price       : price_close * (1.0 - side * offset),
+            placed_at,
+            expires_at  : placed_at + bars.max(1),
+        }
+    }
+
+    /// Returns the fill price within `tick` if its range reaches the limit, the open if the tick gaps through it.
+    fn fill(&self, tick: &TriggerSignal) -> Option<f64> {
+        match self.direction {
+            Direction::Long     => (tick.price_low <= self.price).then(|| self.price.min(tick.price_open)),
+            Direction::Short    => (tick.price_high >= self.price).then(|| self.price.max(tick.price_open)),
+        }
+    }
+
+    /// Returns whether the order is cancelled unfilled after tick `indx`.
+    fn expired(&self, indx: usize) -> bool {
+        indx >= self.expires_at
+    }
 }
 
 /// Returns the average of a list of `Decimal` values.
@@ -415,11 +2062,11 @@ pub fn simulate(sim_params: SimulateParams) -> PnL {
 /// let avg = array_of_decimal_avg(&values);
 /// assert_eq!(avg, 15.0);
 /// ```
-fn array_of_decimal_avg(arr:&Vec<Decimal>) -> f64 {
+fn array_of_decimal_avg<N: Amount>(arr:&[N]) -> f64 {
     if arr.is_empty() { 0.0 }
     else {
-        let sum_values = arr.iter().fold(Decimal::from_f64(0.0).unwrap(), |a, b| a + b);
-        (sum_values / Decimal::from_usize(arr.len()).unwrap()).round_dp(2).to_f64().unwrap()
+        let sum_values = arr.iter().fold(N::ZERO, |a, &b| a + b);
+        (sum_values / N::from_f64_value(arr.len() as f64)).round_dp(2).to_f64_value()
     }
 }
 
@@ -428,13 +2075,13 @@ fn array_of_decimal_avg(arr:&Vec<Decimal>) -> f64 {
 /// This struct encapsulates details about the quantity of assets purchased, the
 /// cost before applying any fees, and the total fees (if any) associated with the simulated purchase.
 #[derive(Debug)]
-struct PurchaseInfo {
+struct PurchaseInfo<N> {
     /// The quantity of assets purchased.
-    asset_qty       : Decimal,
+    asset_qty       : N,
     /// The total cost of the purchase before accounting for fees (if any).
-    cost_before_fee : Decimal,
+    cost_before_fee : N,
     /// The total fees associated with the purchase, if any.
-    total_fee       : Option<Decimal>
+    total_fee       : Option<N>
 }
 
 /// Computes and stages the details for purchasing assets.
@@ -450,33 +2097,33 @@ struct PurchaseInfo {
 /// * `asset_scale`: The scale (number of decimal places) to use when truncating the asset quantity.
 /// * `funds_scale`: The scale to use when truncating the funds value.
 /// * `funds_trade_scale`: Optional scale factor for truncating the funds value when trading.
-fn stage_purchase(
-    funds           : &Decimal,
-    price           : &Decimal,
-    exchange_fee    : &Option<Decimal>,
+fn stage_purchase<N: Amount>(
+    funds           : &N,
+    price           : &N,
+    exchange_fee    : &Option<N>,
 
     asset_scale     : &u32,
     funds_scale     : &u32,
 
     funds_trade_scale   : &Option<u32>,
-) -> PurchaseInfo {
+) -> PurchaseInfo<N> {
     // Determine the available funds after accounting for potential exchange fees
     let funds_available = exchange_fee
-        .map_or(*funds, |fee| (funds - (funds * fee)).trunc_with_scale(*funds_scale));
+        .map_or(*funds, |fee| (*funds - (*funds * fee)).trunc_with_scale(*funds_scale));
 
     // Calculate the quantity of assets that can be purchased with the available funds
-    let mut asset_qty = (funds_available / price).trunc_with_scale(*asset_scale);
+    let mut asset_qty = (funds_available / *price).trunc_with_scale(*asset_scale);
 
-    let mut cost_before_fee = asset_qty * price;
+    let mut cost_before_fee = asset_qty * *price;
 
     // Adjust the cost and asset quantity based on the trade scale, if provided
     if let Some(scale) = funds_trade_scale {
         cost_before_fee = cost_before_fee.trunc_with_scale(*scale);
-        asset_qty = (cost_before_fee / price).trunc_with_scale(*asset_scale);
+        asset_qty = (cost_before_fee / *price).trunc_with_scale(*asset_scale);
     }
 
     // Calculate the total fees, if any
-    let total_fee = exchange_fee.map_or(None, |fee| Some(cost_before_fee * fee));
+    let total_fee = exchange_fee.map(|fee| cost_before_fee * fee);
 
     PurchaseInfo { asset_qty, cost_before_fee, total_fee, }
 }
@@ -487,13 +2134,13 @@ fn stage_purchase(
 /// proceeds from the sale before applying any fees, and the total fees (if any)
 /// deducted from the asset quantity before the sale.
 #[derive(Debug)]
-struct SaleInfo {
+struct SaleInfo<N> {
     /// The quantity of assets sold.
-    assets_sold     : Decimal,
+    assets_sold     : N,
     /// The proceeds from the sale before accounting for fees.
-    sale_before_fee : Decimal,
+    sale_before_fee : N,
     /// The total quantity of assets deducted as fees, if applicable.
-    fee_asset_total : Option<Decimal>,
+    fee_asset_total : Option<N>,
 }
 /// Computes and stages the details for selling assets.
 ///
@@ -508,19 +2155,19 @@ struct SaleInfo {
 /// * `asset_scale`: The scale (number of decimal places) to use when truncating the asset quantity.
 /// * `funds_scale`: The scale to use when truncating the proceeds from the sale.
 /// * `asset_trade_scale`: Optional scale factor for truncating the asset quantity when trading.
-fn stage_sale(
-    asset_qty   : &Decimal,
-    price       : &Decimal,
-    exchange_fee: &Option<Decimal>,
+fn stage_sale<N: Amount>(
+    asset_qty   : &N,
+    price       : &N,
+    exchange_fee: &Option<N>,
 
     asset_scale     : &u32,
     funds_scale     : &u32,
 
     asset_trade_scale   : &Option<u32>,
-) -> SaleInfo {
+) -> SaleInfo<N> {
     // Calculate the net quantity of assets to be sold after accounting for potential exchange fees
     let mut assets_sold = exchange_fee
-        .map_or(*asset_qty, |fee| (asset_qty - (asset_qty * fee)).trunc_with_scale(*asset_scale));
+        .map_or(*asset_qty, |fee| (*asset_qty - (*asset_qty * fee)).trunc_with_scale(*asset_scale));
 
     // Adjust the assets quantity based on the trade scale, if provided
     if let Some(trade_scale) = asset_trade_scale {
@@ -528,14 +2175,37 @@ fn stage_sale(
     }
 
     // Calculate the proceeds from the sale before fees
-    let sale_before_fee = (assets_sold * price).trunc_with_scale(*funds_scale);
+    let sale_before_fee = (assets_sold * *price).trunc_with_scale(*funds_scale);
 
     // Determine the total asset quantity deducted as fees, if any
-    let fee_asset_total = exchange_fee.map_or(None, |fee| Some(assets_sold * fee));
+    let fee_asset_total = exchange_fee.map(|fee| assets_sold * fee);
 
     SaleInfo {assets_sold, sale_before_fee, fee_asset_total}
 }
 
+/// Computes and stages the details for buying back the assets of a short position.
+///
+/// Unlike [`stage_purchase`], the quantity is fixed by the borrowed assets, and the fee is charged in funds on top of the cost.
+///
+/// # Parameters
+///
+/// * `asset_qty`: The quantity of assets to be bought back.
+/// * `price`: The price of a single unit of the asset.
+/// * `exchange_fee`: The optional exchange fee that is applied to the purchase.
+/// * `funds_scale`: The scale to use when truncating the cost of the purchase.
+fn stage_cover<N: Amount>(
+    asset_qty   : &N,
+    price       : &N,
+    exchange_fee: &Option<N>,
+
+    funds_scale : &u32,
+) -> PurchaseInfo<N> {
+    let cost_before_fee = (*asset_qty * *price).trunc_with_scale(*funds_scale);
+    let total_fee = exchange_fee.map(|fee| cost_before_fee * fee);
+
+    PurchaseInfo { asset_qty: *asset_qty, cost_before_fee, total_fee, }
+}
+
 /// Calculates the return from a buy-and-hold trading strategy.
 ///
 /// This function computes the net return of buying an asset at an entry price
@@ -550,21 +2220,24 @@ fn stage_sale(
 /// - `funds_scale`: The scale (precision) for the funds.
 /// - `funds_trade_scale`: The optional trade scale for funds.
 /// - `asset_trade_scale`: The optional trade scale for the asset.
+/// - `fee_in_quote`: Whether the fee of the sale is charged in funds rather than withheld from the asset, see [`FeeCurrency`].
 ///
 /// # Returns
 /// The net return from the buy-and-hold strategy, rounded to two decimal places.
 #[allow(clippy::too_many_arguments)]
-fn buy_and_hold_return(
-    funds       : &Decimal,
-    exchange_fee: &Option<Decimal>,
-    price_entry : &Decimal,
-    price_exit  : &Decimal,
+fn buy_and_hold_return<N: Amount>(
+    funds       : &N,
+    exchange_fee: &Option<N>,
+    price_entry : &N,
+    price_exit  : &N,
 
     asset_scale     : &u32,
     funds_scale     : &u32,
 
     funds_trade_scale   : &Option<u32>,
     asset_trade_scale   : &Option<u32>,
+
+    fee_in_quote        : bool,
 ) -> f64 {
 
     let purchase = stage_purchase(
@@ -575,27 +2248,75 @@ fn buy_and_hold_return(
         funds_scale,
         funds_trade_scale,
     );
-    let mut position = funds - purchase.cost_before_fee;
+    let mut position = *funds - purchase.cost_before_fee;
     if let Some(fee) = purchase.total_fee { position -= fee; }
 
 
     let sale = stage_sale(
         &purchase.asset_qty,
         price_exit,
-        exchange_fee,
+        if fee_in_quote { &None } else { exchange_fee },
         asset_scale,
         funds_scale,
         asset_trade_scale,
     );
 
     position += sale.sale_before_fee;
-    if let Some(fee) = sale.fee_asset_total {
-        position -= (fee * price_exit).trunc_with_scale(*funds_scale);
+    if fee_in_quote {
+        if let Some(fee) = exchange_fee { position -= sale.sale_before_fee * *fee; }
+    } else if let Some(fee) = sale.fee_asset_total {
+        position -= (fee * *price_exit).trunc_with_scale(*funds_scale);
     }
 
-    position += ((purchase.asset_qty - sale.assets_sold) * price_exit).trunc_with_scale(*funds_scale);
+    position += ((purchase.asset_qty - sale.assets_sold) * *price_exit).trunc_with_scale(*funds_scale);
+
+    (position - *funds).round_dp(2).to_f64_value()
+}
+
+/// Calculates the return from a sell-and-hold strategy, the benchmark of [`PositionMode::ShortOnly`].
+///
+/// The asset is sold short at the entry price, sized as if purchased with `funds`, and bought back at the exit price.
+/// Fees are charged in funds on both sides.
+///
+/// # Parameters
+/// - `funds`: The initial funds backing the short sale.
+/// - `exchange_fee`: The optional fee incurred during the transaction.
+/// - `price_entry`: The price at which the asset is sold.
+/// - `price_exit`: The price at which the asset is bought back.
+/// - `asset_scale`: The scale (precision) for the asset quantity.
+/// - `funds_scale`: The scale (precision) for the funds.
+/// - `funds_trade_scale`: The optional trade scale for funds.
+///
+/// # Returns
+/// The net return from the sell-and-hold strategy, rounded to two decimal places.
+fn sell_and_hold_return<N: Amount>(
+    funds       : &N,
+    exchange_fee: &Option<N>,
+    price_entry : &N,
+    price_exit  : &N,
+
+    asset_scale     : &u32,
+    funds_scale     : &u32,
+
+    funds_trade_scale   : &Option<u32>,
+) -> f64 {
+    let sale = stage_purchase(
+        funds,
+        price_entry,
+        exchange_fee,
+        asset_scale,
+        funds_scale,
+        funds_trade_scale,
+    );
+    let mut position = sale.cost_before_fee;
+    if let Some(fee) = sale.total_fee { position -= fee; }
+
+    let cover = stage_cover(&sale.asset_qty, price_exit, exchange_fee, funds_scale);
+
+    position -= cover.cost_before_fee;
+    if let Some(fee) = cover.total_fee { position -= fee; }
 
-    (position - funds).round_dp(2).to_f64().unwrap()
+    position.round_dp(2).to_f64_value()
 }
 
 /// Calculates the profit factor of a set of trades.
@@ -611,12 +2332,12 @@ fn buy_and_hold_return(
 /// # Returns
 /// An `Option` containing the profit factor rounded to three decimal places.
 /// If the total loss is effectively zero (close to machine epsilon), it returns `None`.
-fn profit_factor(profitable_trades: &[Decimal], losing_trades: &[Decimal]) -> Option<f64> {
-    let total_profit = profitable_trades.iter().fold(Decimal::from_f64(0.0).unwrap(), |a, b| a + b);
-    let total_loss = losing_trades.iter().fold(Decimal::from_f64(0.0).unwrap(), |a, b| a + b);
+fn profit_factor<N: Amount>(profitable_trades: &[N], losing_trades: &[N]) -> Option<f64> {
+    let total_profit = profitable_trades.iter().fold(N::ZERO, |a, &b| a + b);
+    let total_loss = losing_trades.iter().fold(N::ZERO, |a, &b| a + b);
 
-    if total_loss.abs() > Decimal::try_from(f64::EPSILON).unwrap() { // Check if total_loss is not effectively zero
-        Some((total_profit / total_loss.abs()).round_dp(3).to_f64().unwrap())
+    if total_loss.abs() > N::from_f64_value(f64::EPSILON) { // Check if total_loss is not effectively zero
+        Some((total_profit / total_loss.abs()).round_dp(3).to_f64_value())
     } else {
         None
     }
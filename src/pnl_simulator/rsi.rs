@@ -0,0 +1,154 @@
+use std::{
+    cmp::Ordering,
+    ops::RangeInclusive,
+};
+
+use crate::oscillators::{models::Hlc, rsi::rsi_for_ticks};
+use super::{
+    optimizer::{OscillatorSetup, Optimizer},
+    search::SearchSpace,
+};
+
+/// `RsiParams` represents the configuration parameters used for Profit and Loss (PnL) simulations
+/// when utilizing the Relative Strength Index. Entries are taken when the RSI recovers from the
+/// oversold zone, exits when it falls back from the overbought zone. See [`threshold_line`].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct RsiParams {
+    /// `length` denotes the number of periods used for the Wilder smoothing of gains and losses.
+    pub length      : u16,
+    /// `overbought` is the RSI level above which the asset is considered overbought.
+    /// Falling back below it after being above closes the position.
+    pub overbought  : u16,
+    /// `oversold` is the RSI level below which the asset is considered oversold.
+    /// Rising back above it after being below opens the position.
+    pub oversold    : u16,
+}
+impl PartialOrd for RsiParams {
+    /// Provides a mechanism to compare two [`RsiParams`], see [`RsiParams::cmp`].
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for RsiParams {
+    /// Compares two [`RsiParams`] for ordering. The comparison starts with `length`, followed by
+    /// `overbought`, and then `oversold`.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.length.cmp(&other.length)
+            .then_with(|| self.overbought.cmp(&other.overbought))
+            .then_with(|| self.oversold.cmp(&other.oversold))
+    }
+}
+
+/// Defines the range of parameters for the RSI used in the PnL simulations.
+/// # Example
+/// ```
+/// use oscillatorsetups::pnl_simulator::rsi::RsiRange;
+///
+/// let rsi_range = RsiRange {
+///     length      : 2..=30,
+///     overbought  : 60..=85,
+///     oversold    : 15..=40,
+/// };
+///
+/// assert_eq!(rsi_range.length     , 2..=30);
+/// assert_eq!(rsi_range.overbought , 60..=85);
+/// assert_eq!(rsi_range.oversold   , 15..=40);
+/// ```
+#[derive(Debug)]
+pub struct RsiRange {
+    /// The inclusive range for length.
+    pub length      : RangeInclusive<u16>,
+    /// The inclusive range for overbought level.
+    pub overbought  : RangeInclusive<u16>,
+    /// The inclusive range for oversold level.
+    pub oversold    : RangeInclusive<u16>,
+}
+
+/// Builds the threshold line the RSI is compared against when generating trigger signals.
+///
+/// The simulator enters while `signal_in > signal_out` and exits while `signal_in < signal_out`.
+/// With the RSI as `signal_in`, this line acts as `signal_out` and follows a small state machine:
+/// - It starts at 100, so no entry is possible until the RSI has been oversold.
+/// - Once the RSI drops below `oversold`, the line is set to `oversold`. Recovering above it triggers an entry,
+///   dropping below it again triggers an exit.
+/// - Once the RSI rises above `overbought` (while the line is at `oversold`), the line is set to `overbought`.
+///   Falling back below it triggers the exit, after which the line is reset to 100.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::rsi::threshold_line;
+///
+/// let rsi = vec![None, Some(50.0), Some(25.0), Some(40.0), Some(75.0), Some(65.0), Some(50.0)];
+///
+/// assert_eq!(
+///     threshold_line(&rsi, 70.0, 30.0),
+///     vec![None, Some(100.0), Some(100.0), Some(30.0), Some(30.0), Some(70.0), Some(100.0)]
+/// );
+/// ```
+pub fn threshold_line(rsi: &[Option<f64>], overbought: f64, oversold: f64) -> Vec<Option<f64>> {
+    let mut threshold = 100.0;
+
+    rsi.iter()
+        .map(|value| {
+            let value = (*value)?;
+            let line = threshold;
+
+            if value < oversold {
+                threshold = oversold;
+            } else if threshold == oversold && value > overbought {
+                threshold = overbought;
+            } else if threshold == overbought && value < overbought {
+                threshold = 100.0;
+            }
+            Some(line)
+        })
+        .collect()
+}
+
+impl OscillatorSetup for RsiParams {
+    type Range = RsiRange;
+
+    /// Expands [`RsiRange`] into every combination where `oversold` is below `overbought`.
+    fn configurations(range: &RsiRange) -> Vec<Self> {
+        range.length.clone().flat_map(|length| {
+            range.overbought.clone().flat_map(move |overbought| {
+                range.oversold.clone()
+                    .filter(move |&oversold| oversold < overbought)
+                    .map(move |oversold| RsiParams { length, overbought, oversold })
+            })
+        }).collect()
+    }
+
+    /// Uses the RSI as `signal_in` and its [`threshold_line`] as `signal_out`.
+    fn signal_lines(&self, price_data: &[Hlc]) -> (Vec<Option<f64>>, Vec<Option<f64>>) {
+        let rsi_line = rsi_for_ticks(price_data, self.length);
+        let threshold = threshold_line(&rsi_line, self.overbought as f64, self.oversold as f64);
+        (rsi_line, threshold)
+    }
+}
+
+impl SearchSpace for RsiParams {
+    /// Bounds `length`, `overbought` and `oversold`, in this order.
+    fn bounds(range: &RsiRange) -> Vec<RangeInclusive<u16>> {
+        vec![range.length.clone(), range.overbought.clone(), range.oversold.clone()]
+    }
+
+    /// Leaves out the combinations where `oversold` is not below `overbought`, as [`RsiParams::configurations`].
+    fn from_params(params: &[u16]) -> Option<Self> {
+        match *params {
+            [length, overbought, oversold] if oversold < overbought => Some(RsiParams { length, overbought, oversold }),
+            _ => None,
+        }
+    }
+
+    fn params(&self) -> Vec<u16> {
+        vec![self.length, self.overbought, self.oversold]
+    }
+}
+
+/// Represents an RSI simulation for a given financial exchange.
+/// The Relative Strength Index is a momentum oscillator measuring the speed and magnitude of recent price changes,
+/// moving between 0 and 100 to flag overbought and oversold conditions.
+///
+/// See [`Optimizer`] for the available settings and methods, e.g. [`Optimizer::pnl`] and [`Optimizer::top_net_profit`].
+pub type Rsi<'a> = Optimizer<'a, RsiParams>;
@@ -0,0 +1,230 @@
+use std::{
+    collections::BTreeMap,
+    ops::RangeInclusive,
+    time::{Duration, Instant},
+};
+
+use super::{
+    optimizer::{Optimizer, Profit},
+    search::{span, Rng, SearchSpace, MAX_ATTEMPTS},
+};
+
+/// How [`Optimizer::sample`] draws configurations from a range.
+///
+/// # Variants
+/// - `Random`: Every parameter is drawn uniformly within its range, the default.
+/// - `LatinHypercube`: The range of every parameter is split into as many equal strata as configurations in a batch,
+///   and each stratum is drawn at most once, covering every parameter evenly even with few configurations.
+///   Invalid parameter combinations are redrawn within the same strata, or left out of the batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Sampling {
+    #[default]
+    Random,
+    LatinHypercube,
+}
+
+/// When [`Optimizer::sample`] stops drawing configurations.
+///
+/// # Variants
+/// - `Evaluations`: After this number of configurations was drawn.
+/// - `Duration`: Once this wall-clock time has elapsed, checked between batches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Budget {
+    Evaluations(usize),
+    Duration(Duration),
+}
+
+/// Settings of the sampled search of [`Optimizer::sample`].
+///
+/// # Fields
+/// - `sampling`    : Default ([`Sampling::Random`]). How configurations are drawn.
+/// - `budget`      : Default ([`Budget::Evaluations`] of 1000). When to stop drawing configurations.
+/// - `batch`       : Default (64). The number of configurations drawn and simulated in parallel at a time.
+/// - `seed`        : Default (0). Seeds the random number generator, the same seed yields the same configurations.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::sampler::{Budget, Sampler, Sampling};
+/// use std::time::Duration;
+///
+/// let sampler = Sampler {
+///     sampling    : Sampling::LatinHypercube,
+///     budget      : Budget::Duration(Duration::from_secs(8 * 3600)),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(sampler.batch, 64);
+/// assert_eq!(Sampler::default().budget, Budget::Evaluations(1000));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sampler {
+    pub sampling    : Sampling,
+    pub budget      : Budget,
+    pub batch       : usize,
+    pub seed        : u64,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler {
+            sampling    : Sampling::Random,
+            budget      : Budget::Evaluations(1000),
+            batch       : 64,
+            seed        : 0,
+        }
+    }
+}
+
+impl Sampler {
+    /// Draws configurations of `range` in batches until the budget is spent, scoring them with `score`, see [`Optimizer::sample`].
+    ///
+    /// Every batch, `score` is called once with the configurations drawn not scored before, each one once,
+    /// and returns their scores, `None` for a configuration failing the constraints.
+    /// Batches only redrawing scored configurations count towards the budget, but once `MAX_ATTEMPTS` (100) of them
+    /// follow each other the range is deemed exhausted and the search ends early.
+    ///
+    /// # Returns
+    /// - Every scored configuration with its score.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use oscillatorsetups::pnl_simulator::rsi::{RsiParams, RsiRange};
+    /// use oscillatorsetups::pnl_simulator::sampler::{Budget, Sampler, Sampling};
+    ///
+    /// let score = |setups: Vec<RsiParams>| -> BTreeMap<RsiParams, Option<f64>> {
+    ///     setups.into_iter().map(|setup| (setup.clone(), Some(setup.length as f64))).collect()
+    /// };
+    ///
+    /// // Half of the levels overlap, so many draws put the oversold level at or above the overbought one
+    /// let range = || RsiRange { length: 2..=41, overbought: 50..=89, oversold: 30..=69 };
+    /// let sampler = Sampler { sampling: Sampling::LatinHypercube, budget: Budget::Evaluations(8), batch: 8, seed: 3 };
+    /// let mut batches = Vec::new();
+    /// let evaluated = sampler.search(&range(), |setups: Vec<RsiParams>| { batches.push(setups.clone()); score(setups) });
+    /// assert!(evaluated.keys().all(|setup| setup.oversold < setup.overbought));
+    ///
+    /// // No oversold level of one draw's strata lies below its overbought level, the budget left is drawn in another batch
+    /// assert_eq!(batches.iter().map(Vec::len).collect::<Vec<usize>>(), [7, 1]);
+    ///
+    /// // Every valid configuration of the first batch takes its own of the 8 strata, 5 values wide, of every parameter
+    /// let first = &batches[0];
+    /// for (values, start) in [(first.iter().map(|setup| setup.length).collect::<Vec<u16>>(), 2), (first.iter().map(|setup| setup.overbought).collect(), 50)] {
+    ///     let mut strata: Vec<u16> = values.iter().map(|value| (value - start) / 5).collect();
+    ///     strata.sort();
+    ///     strata.dedup();
+    ///     assert_eq!(strata.len(), first.len());
+    /// }
+    /// assert_eq!(sampler.search(&range(), score), evaluated);
+    ///
+    /// // Batches of known configurations do not end the search before the 10 configurations of the range are found
+    /// let range = RsiRange { length: 14..=14, overbought: 70..=73, oversold: 69..=72 };
+    /// let sampler = Sampler { budget: Budget::Evaluations(1000), batch: 1, ..Default::default() };
+    /// assert_eq!(sampler.search(&range, score).len(), 10);
+    /// ```
+    pub fn search<S: SearchSpace>(&self, range: &S::Range, mut score: impl FnMut(Vec<S>) -> BTreeMap<S, Option<f64>>) -> BTreeMap<S, Option<f64>> {
+        let mut evaluated = BTreeMap::new();
+        let bounds = S::bounds(range);
+        if bounds.iter().any(|bounds| bounds.is_empty()) { return evaluated; }
+
+        let started = Instant::now();
+        let mut rng = Rng::new(self.seed);
+        let mut drawn = 0;
+        let mut exhausted = 0;
+
+        while exhausted < MAX_ATTEMPTS {
+            let batch = match self.budget {
+                Budget::Evaluations(evaluations)    => self.batch.max(1).min(evaluations - drawn),
+                Budget::Duration(duration)          => if started.elapsed() < duration { self.batch.max(1) } else { 0 },
+            };
+            if batch == 0 { break; }
+
+            let mut setups: Vec<S> = match self.sampling {
+                Sampling::Random            => (0..batch * MAX_ATTEMPTS)
+                    .filter_map(|_| S::from_params(&bounds.iter().map(|bounds| rng.within(bounds)).collect::<Vec<u16>>()))
+                    .take(batch)
+                    .collect(),
+                Sampling::LatinHypercube    => latin_hypercube(&bounds, batch, &mut rng),
+            };
+            drawn += setups.len();
+
+            setups.retain(|setup| !evaluated.contains_key(setup));
+            setups.sort();
+            setups.dedup();
+            if setups.is_empty() { exhausted += 1; continue; }
+
+            exhausted = 0;
+            evaluated.extend(score(setups));
+        }
+
+        evaluated
+    }
+}
+
+impl<S: SearchSpace> Optimizer<'_, S> {
+    /// Searches `range` by simulating sampled configurations until the budget is spent,
+    /// for ranges too wide for [`Optimizer::top_net_profit`] to simulate exhaustively.
+    ///
+    /// Configurations are drawn in batches of `batch`, each simulated in parallel and scored once
+    /// by the `objective` and `constraints` of this instance. Invalid parameter combinations are redrawn,
+    /// configurations drawn more than once count towards the budget but are simulated once. See [`Sampler::search`].
+    ///
+    /// # Parameters
+    /// - `range`: The ranges of parameters to search, e.g. [`crate::pnl_simulator::stochastic::PnlRange`].
+    /// - `sampler`: The [`Sampler`] settings.
+    ///
+    /// # Returns
+    /// - The top `top_n` configurations found until the budget was spent with their score,
+    ///   in ascending order as [`Optimizer::top_net_profit`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oscillatorsetups::exchange::chart_data::klines::{Intervals, KlinesSubset};
+    /// use oscillatorsetups::pnl_simulator::sampler::{Budget, Sampler, Sampling};
+    /// use oscillatorsetups::pnl_simulator::stochastic::{PnlRange, Stochastic};
+    ///
+    /// // A 12 kline cycle, sampled over a range of 1.3 million configurations
+    /// let klines: Vec<KlinesSubset> = (0..300u64).map(|ix| {
+    ///     let price = 100.0 + 10.0 * (ix as f64 * std::f64::consts::TAU / 12.0).sin();
+    ///     KlinesSubset { time_open: ix * 3_600_000, price_open: price, price_low: price - 1.0, price_high: price + 1.0, price_close: price, time_close: ix * 3_600_000 + 3_599_999, volume: 1.0 }
+    /// }).collect();
+    /// let stochastic = Stochastic::from_klines("binance", klines, Intervals::H1).top_n(5);
+    ///
+    /// let range = || PnlRange { k_length: 3..=200, k_smoothing: 3..=80, d_length: 3..=80 };
+    /// let sampler = Sampler { sampling: Sampling::LatinHypercube, budget: Budget::Evaluations(256), ..Default::default() };
+    ///
+    /// let top_profits = stochastic.sample(range(), sampler.clone());
+    /// assert_eq!(top_profits.len(), 5);
+    /// assert!(top_profits.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    /// assert_eq!(top_profits, stochastic.sample(range(), sampler));
+    /// ```
+    pub fn sample(&self, range: S::Range, sampler: Sampler) -> Vec<(Profit, S)> {
+        let evaluated = sampler.search(&range, |setups| {
+            let mut scores = BTreeMap::new();
+            self.evaluate(setups, &mut scores);
+            scores
+        });
+
+        self.top_evaluated(&evaluated)
+    }
+}
+
+/// Draws up to `len` configurations within `bounds`, each parameter taking every one of `len` equal strata of its range at most once.
+///
+/// A configuration whose parameters are invalid is redrawn within the same strata, and left out if none of `MAX_ATTEMPTS` redraws is valid,
+/// so the valid configurations stay stratified while a batch may fall short of `len`.
+fn latin_hypercube<S: SearchSpace>(bounds: &[RangeInclusive<u16>], len: usize, rng: &mut Rng) -> Vec<S> {
+    let columns: Vec<Vec<usize>> = bounds.iter().map(|_| {
+        let mut strata: Vec<usize> = (0..len).collect();
+        for ix in (1..len).rev() { strata.swap(ix, rng.below(ix + 1)); }
+        strata
+    }).collect();
+
+    (0..len).filter_map(|ix| (0..MAX_ATTEMPTS).find_map(|_| {
+        let params: Vec<u16> = columns.iter().zip(bounds).map(|(strata, bounds)| {
+            let offset = ((strata[ix] as f64 + rng.unit()) / len as f64 * span(bounds) as f64) as usize;
+            bounds.start() + offset.min(span(bounds) - 1) as u16
+        }).collect();
+        S::from_params(&params)
+    })).collect()
+}
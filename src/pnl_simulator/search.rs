@@ -0,0 +1,113 @@
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use super::optimizer::{OscillatorSetup, Optimizer, Profit};
+
+use rayon::prelude::*;
+
+/// The number of attempts to draw or breed a valid configuration before falling back or giving up.
+pub(super) const MAX_ATTEMPTS: usize = 100;
+
+/// An [`OscillatorSetup`] whose configurations are points of a space of integer parameters,
+/// searched by the optimizers which sample configurations instead of expanding the whole range,
+/// e.g. [`Optimizer::genetic`].
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::search::SearchSpace;
+/// use oscillatorsetups::pnl_simulator::stochastic::{PnlParams, PnlRange};
+///
+/// let range = PnlRange { k_length: 5..=20, k_smoothing: 3..=12, d_length: 3..=12 };
+/// assert_eq!(PnlParams::bounds(&range), vec![5..=20, 3..=12, 3..=12]);
+///
+/// let setup = PnlParams::from_params(&[14, 3, 3]).unwrap();
+/// assert_eq!(setup, PnlParams { k_length: 14, k_smoothing: 3, d_length: 3 });
+/// assert_eq!(setup.params(), vec![14, 3, 3]);
+/// ```
+pub trait SearchSpace: OscillatorSetup {
+    /// The inclusive bounds of every parameter of `range`, in the order of [`SearchSpace::params`].
+    fn bounds(range: &Self::Range) -> Vec<RangeInclusive<u16>>;
+
+    /// Builds the configuration with the parameter values `params`.
+    ///
+    /// # Returns
+    /// - `None` if the values do not form a valid configuration, e.g. an oversold level above the overbought one.
+    fn from_params(params: &[u16]) -> Option<Self>;
+
+    /// The parameter values of this configuration, in the order of [`SearchSpace::bounds`].
+    fn params(&self) -> Vec<u16>;
+}
+
+/// A seeded pseudo-random number generator (SplitMix64), so searches are reproducible across runs.
+#[derive(Debug, Clone)]
+pub(super) struct Rng(u64);
+
+impl Rng {
+    pub(super) fn new(seed: u64) -> Self { Rng(seed) }
+
+    pub(super) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform value in `[0, 1)`.
+    pub(super) fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A standard normal value (Box-Muller).
+    pub(super) fn normal(&mut self) -> f64 {
+        (-2.0 * (1.0 - self.unit()).ln()).sqrt() * (std::f64::consts::TAU * self.unit()).cos()
+    }
+
+    /// A uniform index below `len`, which must not be 0.
+    pub(super) fn below(&mut self, len: usize) -> usize {
+        ((self.next_u64() as u128 * len as u128) >> 64) as usize
+    }
+
+    /// A uniform value within `bounds`.
+    pub(super) fn within(&mut self, bounds: &RangeInclusive<u16>) -> u16 {
+        bounds.start() + self.below(span(bounds)) as u16
+    }
+}
+
+/// The number of values within `bounds`.
+pub(super) fn span(bounds: &RangeInclusive<u16>) -> usize {
+    (*bounds.end() as usize + 1).saturating_sub(*bounds.start() as usize)
+}
+
+impl<S: SearchSpace> Optimizer<'_, S> {
+    /// Scores every configuration of `setups` not in `evaluated` yet in parallel, and adds it to `evaluated`.
+    /// A configuration failing the `constraints` is recorded with a `None` score, so it is not simulated again.
+    pub(super) fn evaluate(&self, setups: Vec<S>, evaluated: &mut BTreeMap<S, Option<f64>>) {
+        let mut setups: Vec<S> = setups.into_iter().filter(|setup| !evaluated.contains_key(setup)).collect();
+        setups.sort();
+        setups.dedup();
+
+        let scores: Vec<(S, Option<f64>)> = setups
+            .into_par_iter()
+            .map(|setup| {
+                let (signal_in, signal_out) = setup.signal_lines(&self.lhc);
+                let score = self.screen(&signal_in, &signal_out, 0..self.klines.len());
+                (setup, score)
+            })
+            .collect();
+
+        evaluated.extend(scores);
+    }
+
+    /// Returns the `top_n` configurations of `evaluated` in ascending order of their score,
+    /// as [`Optimizer::top_net_profit`] does for the whole range.
+    pub(super) fn top_evaluated(&self, evaluated: &BTreeMap<S, Option<f64>>) -> Vec<(Profit, S)> {
+        let mut top_profits: Vec<(Profit, S)> = evaluated
+            .iter()
+            .filter_map(|(setup, score)| Some((Profit((*score)?), setup.clone())))
+            .collect();
+        top_profits.sort();
+        let top_profits = top_profits.split_off(top_profits.len().saturating_sub(self.top_n));
+
+        self.rescored(top_profits, 0..self.klines.len())
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    f64::consts::TAU,
+    ops::RangeInclusive,
+};
+
+use super::{
+    optimizer::{Optimizer, Profit},
+    search::{span, Rng, SearchSpace, MAX_ATTEMPTS},
+};
+
+/// Settings of the Tree-structured Parzen Estimator of [`TpeSearch`] and [`Optimizer::tpe`].
+///
+/// # Fields
+/// - `evaluations` : Default (200). The number of distinct configurations simulated in total.
+/// - `startup`     : Default (20). The number of configurations drawn at random before the estimator takes over.
+/// - `gamma`       : Default (0.25). The share of the best scored configurations the estimator considers good.
+/// - `candidates`  : Default (24). The number of candidates drawn from the good configurations for every proposal.
+/// - `batch`       : Default (8). The number of configurations proposed and simulated in parallel at a time, 1 for a purely sequential search.
+/// - `seed`        : Default (0). Seeds the random number generator, the same seed yields the same search.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::tpe::Tpe;
+///
+/// let tpe = Tpe { evaluations: 500, seed: 42, ..Default::default() };
+///
+/// assert_eq!(tpe.startup, 20);
+/// assert_eq!(tpe.gamma, 0.25);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tpe {
+    pub evaluations : usize,
+    pub startup     : usize,
+    pub gamma       : f64,
+    pub candidates  : usize,
+    pub batch       : usize,
+    pub seed        : u64,
+}
+
+impl Default for Tpe {
+    fn default() -> Self {
+        Tpe {
+            evaluations : 200,
+            startup     : 20,
+            gamma       : 0.25,
+            candidates  : 24,
+            batch       : 8,
+            seed        : 0,
+        }
+    }
+}
+
+/// A sequential model-based search of a [`SearchSpace`], proposing the next configuration to evaluate from the scores of the previous ones.
+///
+/// The scored configurations are split into the good ones, the `gamma` share with the best scores, and the others.
+/// Each split is modelled by a Parzen estimator: a Gaussian kernel around every configuration, per parameter,
+/// mixed with a uniform prior over the range. Of `candidates` drawn from the good model, the one most likely good
+/// relative to the others is proposed. Configurations without a score count as the worst ones.
+///
+/// [`Optimizer::tpe`] drives the search with simulations, the search itself works with any score.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::search::SearchSpace;
+/// use oscillatorsetups::pnl_simulator::stochastic::{PnlParams, PnlRange};
+/// use oscillatorsetups::pnl_simulator::tpe::{Tpe, TpeSearch};
+///
+/// let range = PnlRange { k_length: 3..=200, k_smoothing: 3..=200, d_length: 3..=200 };
+/// let mut search = TpeSearch::<PnlParams>::new(&range, Tpe { seed: 7, ..Default::default() });
+///
+/// // An expensive objective peaking at k_length 40, k_smoothing 10, d_length 5
+/// let score = |setup: &PnlParams| -> f64 {
+///     setup.params().iter().zip([40.0, 10.0, 5.0]).map(|(&value, peak)| -(value as f64 - peak).abs()).sum()
+/// };
+///
+/// for _ in 0..200 {
+///     let setup = search.propose().unwrap();
+///     let value = score(&setup);
+///     search.observe(setup, Some(value));
+/// }
+///
+/// let (best, value) = search.evaluated().iter().max_by(|a, b| a.1.partial_cmp(b.1).unwrap()).unwrap();
+/// // Close to the peak after 200 of its 7.8 million configurations
+/// assert!(value.unwrap() > -10.0, "{:?} {:?}", best, value);
+/// ```
+#[derive(Debug)]
+pub struct TpeSearch<S> {
+    tpe         : Tpe,
+    bounds      : Vec<RangeInclusive<u16>>,
+    rng         : Rng,
+    evaluated   : BTreeMap<S, Option<f64>>,
+    pending     : BTreeSet<S>,
+}
+
+impl<S: SearchSpace> TpeSearch<S> {
+    /// Creates a search of `range` without any scored configurations.
+    pub fn new(range: &S::Range, tpe: Tpe) -> Self {
+        TpeSearch {
+            bounds      : S::bounds(range),
+            rng         : Rng::new(tpe.seed),
+            evaluated   : BTreeMap::new(),
+            pending     : BTreeSet::new(),
+            tpe,
+        }
+    }
+
+    /// Proposes the next configuration to evaluate, one neither scored nor proposed before.
+    /// Until scored by [`TpeSearch::observe`], a proposal counts as pending.
+    ///
+    /// # Returns
+    /// - `None` if no such configuration was found, e.g. the range is exhausted.
+    pub fn propose(&mut self) -> Option<S> {
+        if self.bounds.iter().any(|bounds| bounds.is_empty()) { return None; }
+
+        let proposal = if self.evaluated.len() + self.pending.len() < self.tpe.startup { None } else { self.estimated() };
+        let proposal = proposal.or_else(|| (0..MAX_ATTEMPTS).find_map(|_| {
+            let params: Vec<u16> = self.bounds.iter().map(|bounds| self.rng.within(bounds)).collect();
+            S::from_params(&params).filter(|setup| self.is_new(setup))
+        }))?;
+
+        self.pending.insert(proposal.clone());
+        Some(proposal)
+    }
+
+    /// Records the `score` of `setup`, greater values are better. `None` for a configuration failing the constraints.
+    pub fn observe(&mut self, setup: S, score: Option<f64>) {
+        self.pending.remove(&setup);
+        self.evaluated.insert(setup, score.filter(|score| !score.is_nan()));
+    }
+
+    /// Every scored configuration.
+    pub fn evaluated(&self) -> &BTreeMap<S, Option<f64>> {
+        &self.evaluated
+    }
+
+    fn is_new(&self, setup: &S) -> bool {
+        !self.evaluated.contains_key(setup) && !self.pending.contains(setup)
+    }
+
+    /// Proposes the candidate of the good estimator most likely good relative to the other estimator.
+    /// `None` if nothing was scored yet or no candidate is new.
+    fn estimated(&mut self) -> Option<S> {
+        let mut ranked: Vec<(&S, f64)> = self.evaluated
+            .iter()
+            .map(|(setup, score)| (setup, score.unwrap_or(f64::NEG_INFINITY)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        if ranked.first().map_or(true, |(_, score)| *score == f64::NEG_INFINITY) { return None; }
+
+        let good_len = ((self.tpe.gamma * ranked.len() as f64).ceil() as usize).clamp(1, ranked.len());
+        let params: Vec<Vec<u16>> = ranked.iter().map(|(setup, _)| setup.params()).collect();
+        let good = Parzen::new(&params[..good_len], &self.bounds);
+        let other = Parzen::new(&params[good_len..], &self.bounds);
+
+        (0..self.tpe.candidates.max(1))
+            .filter_map(|_| {
+                let params = good.sample(&self.bounds, &mut self.rng);
+                let setup = S::from_params(&params).filter(|setup| self.is_new(setup))?;
+                Some((good.log_density(&params) - other.log_density(&params), setup))
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0).then_with(|| b.1.cmp(&a.1)))
+            .map(|(_, setup)| setup)
+    }
+}
+
+/// A Parzen estimator over integer parameters: Gaussian kernels around the observed values of each parameter, mixed with a uniform prior.
+struct Parzen {
+    columns     : Vec<Vec<f64>>,
+    bandwidths  : Vec<f64>,
+    spans       : Vec<f64>,
+}
+
+impl Parzen {
+    fn new(observed: &[Vec<u16>], bounds: &[RangeInclusive<u16>]) -> Self {
+        let columns = (0..bounds.len()).map(|dim| observed.iter().map(|params| params[dim] as f64).collect()).collect();
+        let spans: Vec<f64> = bounds.iter().map(|bounds| span(bounds) as f64).collect();
+        let bandwidths = spans.iter().map(|span| (span * (observed.len() as f64 + 1.0).powf(-0.2) / 4.0).max(1.0)).collect();
+
+        Parzen { columns, bandwidths, spans }
+    }
+
+    /// Draws every parameter from the prior or from the kernel of an observed value, each with equal weight.
+    fn sample(&self, bounds: &[RangeInclusive<u16>], rng: &mut Rng) -> Vec<u16> {
+        self.columns.iter().zip(&self.bandwidths).zip(bounds).map(|((column, bandwidth), bounds)| {
+            let ix = rng.below(column.len() + 1);
+            if ix == column.len() { return rng.within(bounds); }
+
+            let value = (column[ix] + bandwidth * rng.normal()).round();
+            value.clamp(*bounds.start() as f64, *bounds.end() as f64) as u16
+        }).collect()
+    }
+
+    fn log_density(&self, params: &[u16]) -> f64 {
+        self.columns.iter().zip(&self.bandwidths).zip(&self.spans).zip(params).map(|(((column, bandwidth), span), &value)| {
+            let kernels: f64 = column.iter()
+                .map(|observed| (-0.5 * ((value as f64 - observed) / bandwidth).powi(2)).exp() / (bandwidth * TAU.sqrt()))
+                .sum();
+            ((1.0 / span + kernels) / (column.len() as f64 + 1.0)).ln()
+        }).sum()
+    }
+}
+
+impl<S: SearchSpace> Optimizer<'_, S> {
+    /// Searches `range` with a Tree-structured Parzen Estimator, see [`TpeSearch`], for objectives too expensive to simulate
+    /// every configuration of, e.g. with protective exits, realistic costs or long price histories.
+    ///
+    /// Every round proposes `batch` configurations from the scores of the previous ones and simulates them in parallel,
+    /// scored by the `objective` and `constraints` of this instance, until `evaluations` configurations were simulated.
+    ///
+    /// # Parameters
+    /// - `range`: The ranges of parameters to search, e.g. [`crate::pnl_simulator::stochastic::PnlRange`].
+    /// - `tpe`: The [`Tpe`] settings.
+    ///
+    /// # Returns
+    /// - The top `top_n` configurations simulated with their score, in ascending order as [`Optimizer::top_net_profit`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oscillatorsetups::exchange::chart_data::klines::{Intervals, KlinesSubset};
+    /// use oscillatorsetups::pnl_simulator::stochastic::{PnlRange, Stochastic};
+    /// use oscillatorsetups::pnl_simulator::tpe::Tpe;
+    ///
+    /// // A noisy 12 kline cycle, traded with protective exits
+    /// let klines: Vec<KlinesSubset> = (0..300u64).map(|ix| {
+    ///     let price = 100.0 + 10.0 * (ix as f64 * std::f64::consts::TAU / 12.0).sin() + ((ix * 37) % 11) as f64 - 5.0;
+    ///     KlinesSubset { time_open: ix * 3_600_000, price_open: price, price_low: price - 1.0, price_high: price + 1.0, price_close: price, time_close: ix * 3_600_000 + 3_599_999, volume: 1.0 }
+    /// }).collect();
+    /// let stochastic = Stochastic::from_klines("binance", klines, Intervals::H1).stop_loss(0.02).trailing_stop(0.03).top_n(100);
+    ///
+    /// let range = || PnlRange { k_length: 3..=200, k_smoothing: 3..=200, d_length: 3..=200 };
+    /// let tpe = Tpe { evaluations: 40, batch: 8, seed: 42, ..Default::default() };
+    ///
+    /// // Exactly the 40 configurations of the budget are simulated, the same ones for the same seed
+    /// let top_profits = stochastic.tpe(range(), tpe.clone());
+    /// assert_eq!(top_profits.len(), 40);
+    /// assert!(top_profits.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    /// assert_eq!(top_profits, stochastic.tpe(range(), tpe));
+    /// ```
+    pub fn tpe(&self, range: S::Range, tpe: Tpe) -> Vec<(Profit, S)> {
+        let mut search = TpeSearch::new(&range, tpe.clone());
+
+        while search.evaluated().len() < tpe.evaluations {
+            let batch = tpe.batch.max(1).min(tpe.evaluations - search.evaluated().len());
+            let proposals: Vec<S> = (0..batch).map_while(|_| search.propose()).collect();
+            if proposals.is_empty() { break; }
+
+            let mut scores = BTreeMap::new();
+            self.evaluate(proposals, &mut scores);
+            for (setup, score) in scores { search.observe(setup, score); }
+        }
+
+        self.top_evaluated(search.evaluated())
+    }
+}
@@ -0,0 +1,197 @@
+use std::ops::Range;
+
+use super::{
+    models::{Backtest, PnL},
+    optimizer::{OscillatorSetup, Optimizer},
+    pnl::{backtest_windows, simulate},
+};
+
+/// Rolling in-sample/out-of-sample windows over the klines, see [`Optimizer::walk_forward`].
+///
+/// Each window is optimized on `in_sample` klines and traded on the `out_of_sample` klines following them.
+/// The windows roll forward by `out_of_sample` klines, so the out-of-sample parts are contiguous and never overlap.
+///
+/// # Fields
+/// - `in_sample`       : The number of klines each configuration is optimized on.
+/// - `out_of_sample`   : The number of klines the winning configuration is traded on, the last window may be shorter.
+///
+/// # Examples
+/// ```
+/// use oscillatorsetups::pnl_simulator::walk_forward::WalkForward;
+///
+/// let walk_forward = WalkForward { in_sample: 4, out_of_sample: 2 };
+///
+/// assert_eq!(walk_forward.windows(9), vec![(0..4, 4..6), (2..6, 6..8), (4..8, 8..9)]);
+/// assert!(walk_forward.windows(4).is_empty());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalkForward {
+    pub in_sample       : usize,
+    pub out_of_sample   : usize,
+}
+
+impl WalkForward {
+    /// Splits `len` klines into `(in_sample, out_of_sample)` ranges of kline indexes.
+    ///
+    /// # Returns
+    /// - No windows if either length is 0, or `len` does not exceed `in_sample`.
+    pub fn windows(&self, len: usize) -> Vec<(Range<usize>, Range<usize>)> {
+        if self.in_sample == 0 || self.out_of_sample == 0 { return Vec::new(); }
+
+        (self.in_sample..len)
+            .step_by(self.out_of_sample)
+            .map(|start| (start - self.in_sample..start, start..(start + self.out_of_sample).min(len)))
+            .collect()
+    }
+}
+
+/// One window of a walk-forward optimization.
+///
+/// # Fields
+/// - `in_sample`       : The kline indexes the configurations were ranked on.
+/// - `out_of_sample`   : The kline indexes `setup` was traded on.
+/// - `setup`           : The configuration traded out-of-sample. The winner of `in_sample`, or the one of the previous
+///   window if no configuration satisfied the constraints. `None` if no window had a winner yet.
+/// - `in_sample_score` : The score of the in-sample winner, `None` if there was none.
+/// - `pnl`             : The [`PnL`] of `setup` simulated on `out_of_sample` alone, starting flat with the full capital.
+#[derive(Debug)]
+pub struct WalkForwardWindow<S> {
+    pub in_sample       : Range<usize>,
+    pub out_of_sample   : Range<usize>,
+    pub setup           : Option<S>,
+    pub in_sample_score : Option<f64>,
+    pub pnl             : PnL,
+}
+
+/// The outcome of [`Optimizer::walk_forward`].
+///
+/// # Fields
+/// - `windows`     : Every [`WalkForwardWindow`], in chronological order.
+/// - `backtest`    : The out-of-sample parts of all windows simulated as one continuous run, see [`crate::pnl_simulator::pnl::backtest`].
+///   A position still open at the end of a window is closed with [`crate::pnl_simulator::models::ExitReason::WindowEnd`], and
+///   the ATR of the stops and sizing, the trade statistics of the Kelly sizing and the volume of the fee tiers restart with every window.
+///   The funds carry over to the next window, so each window trades as in its own `pnl` with the amounts of the stakes
+///   scaled by the funds, or exactly so with [`crate::pnl_simulator::pnl::Sizing::FixedQuote`]. A run whose funds are
+///   depleted stops trading. The equity curve covers the traded out-of-sample klines only.
+#[derive(Debug)]
+pub struct WalkForwardResult<S> {
+    pub windows     : Vec<WalkForwardWindow<S>>,
+    pub backtest    : Backtest,
+}
+
+impl<S: OscillatorSetup> Optimizer<'_, S> {
+    /// Optimizes `range` on rolling in-sample windows, trading each winner on the following out-of-sample window.
+    ///
+    /// Every window is ranked as in [`Optimizer::top_net_profit`], by the `objective` and `constraints` of this instance.
+    /// The signal lines are calculated over all klines, so the klines before a window serve as the warm-up of the oscillator.
+    ///
+    /// # Parameters
+    /// - `range`: The ranges of parameters to optimize, e.g. [`crate::pnl_simulator::stochastic::PnlRange`].
+    /// - `walk_forward`: The [`WalkForward`] window lengths.
+    ///
+    /// # Returns
+    /// - A [`WalkForwardResult`] with the outcome of every window and the stitched out-of-sample [`Backtest`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oscillatorsetups::exchange::chart_data::klines::{Intervals, KlinesSubset};
+    /// use oscillatorsetups::pnl_simulator::models::ExitReason;
+    /// use oscillatorsetups::pnl_simulator::pnl::{AtrStop, FeeCurrency, FeeTier, Sizing};
+    /// use oscillatorsetups::pnl_simulator::stochastic::{PnlRange, Stochastic};
+    /// use oscillatorsetups::pnl_simulator::walk_forward::WalkForward;
+    ///
+    /// // The price cycles every 12 klines
+    /// let klines: Vec<KlinesSubset> = (0..200u64).map(|ix| {
+    ///     let price = 100.0 + 10.0 * (ix as f64 * std::f64::consts::TAU / 12.0).sin();
+    ///     KlinesSubset { time_open: ix * 3_600_000, price_open: price, price_low: price - 1.0, price_high: price + 1.0, price_close: price, time_close: ix * 3_600_000 + 3_599_999, volume: 1.0 }
+    /// }).collect();
+    /// let stochastic = Stochastic::from_klines("binance", klines.clone(), Intervals::H1);
+    ///
+    /// let range = PnlRange { k_length: 5..=15, k_smoothing: 3..=5, d_length: 3..=5 };
+    /// let walk_forward = WalkForward { in_sample: 60, out_of_sample: 40 };
+    /// let result = stochastic.walk_forward(range, walk_forward);
+    ///
+    /// // Every window is traded right after the klines it was optimized on, the out-of-sample parts follow one another
+    /// let bounds: Vec<_> = result.windows.iter().map(|window| (window.in_sample.clone(), window.out_of_sample.clone())).collect();
+    /// assert_eq!(bounds, walk_forward.windows(200));
+    /// assert_eq!(bounds[0], (0..60, 60..100));
+    /// for window in &result.windows {
+    ///     assert_eq!(window.in_sample.end, window.out_of_sample.start);
+    ///     assert!(window.setup.is_some() && window.in_sample_score.is_some());
+    /// }
+    ///
+    /// // The stitched backtest only trades the out-of-sample klines, as one run
+    /// let backtest = &result.backtest;
+    /// assert_eq!(backtest.equity.len(), 140);
+    /// assert!(backtest.trades.iter().all(|trade| trade.entry_time >= klines[60].time_open));
+    ///
+    /// // Positions are closed at the end of every window, so the run makes the trades of the windows
+    /// assert!(backtest.trades.iter().any(|trade| trade.exit_reason == ExitReason::WindowEnd));
+    /// let window_trades: i32 = result.windows.iter().map(|window| window.pnl.total_closed_trades).sum();
+    /// assert_eq!(backtest.pnl.total_closed_trades, window_trades);
+    /// for trade in &backtest.trades {
+    ///     let window = result.windows.iter().find(|window| klines[window.out_of_sample.clone()].iter().any(|kline| kline.time_open == trade.entry_time)).unwrap();
+    ///     assert!(trade.exit_time <= klines[window.out_of_sample.end - 1].time_open);
+    /// }
+    /// let profits: f64 = backtest.trades.iter().map(|trade| trade.profit).sum();
+    /// assert!((backtest.pnl.net_profit - profits).abs() < 1e-6);
+    /// assert!((backtest.equity.last().unwrap() - (1000.0 + backtest.pnl.net_profit)).abs() < 1e-6);
+    ///
+    /// // Staking fixed amounts and paying the fees in quote, which leaves no dust of the asset to the next trade, the run matches
+    /// // the windows trade for trade: the ATR of the stops and the traded volume of the fee tiers restart with every window,
+    /// // as when it is simulated on its own. Wicks every 12 klines hit the stops
+    /// let wicked: Vec<KlinesSubset> = klines.iter().enumerate()
+    ///     .map(|(ix, kline)| KlinesSubset { price_low: if ix % 12 == 0 { kline.price_low - 14.0 } else { kline.price_low }, ..kline.clone() })
+    ///     .collect();
+    /// let tiers = vec![FeeTier { min_volume: 0.0, maker: 0.001, taker: 0.001 }, FeeTier { min_volume: 5_000.0, maker: 0.0005, taker: 0.0005 }];
+    /// let guarded = Stochastic::from_klines("binance", wicked.clone(), Intervals::H1)
+    ///     .sizing(Sizing::FixedQuote(500.0))
+    ///     .atr_stop(AtrStop { length: 14, multiple: 1.5 })
+    ///     .fee_tiers(tiers)
+    ///     .fee_currency(FeeCurrency::Quote)
+    ///     .walk_forward(PnlRange { k_length: 5..=15, k_smoothing: 3..=5, d_length: 3..=5 }, walk_forward);
+    ///
+    /// assert!(guarded.backtest.trades.iter().any(|trade| trade.exit_reason == ExitReason::AtrStop));
+    /// for window in &guarded.windows {
+    ///     let times = wicked[window.out_of_sample.start].time_open..=wicked[window.out_of_sample.end - 1].time_close;
+    ///     let trades: Vec<_> = guarded.backtest.trades.iter().filter(|trade| times.contains(&trade.entry_time)).collect();
+    ///     assert_eq!(trades.len() as i32, window.pnl.total_closed_trades);
+    ///     assert_eq!(trades.iter().filter(|trade| trade.profit > 0.0).count() as i32, window.pnl.num_winning_trades);
+    ///     let profit: f64 = trades.iter().map(|trade| trade.profit).sum();
+    ///     assert!((profit - window.pnl.net_profit).abs() < 1e-6);
+    /// }
+    /// ```
+    pub fn walk_forward(&self, range: S::Range, walk_forward: WalkForward) -> WalkForwardResult<S> {
+        let windows = walk_forward.windows(self.klines.len());
+        let in_samples: Vec<Range<usize>> = windows.iter().map(|(in_sample, _)| in_sample.clone()).collect();
+        let ranked = self.rank(&range, &in_samples);
+
+        let mut setup: Option<S> = None;
+        let mut signals = Vec::new();
+        // Index of the last signal of every traded window
+        let mut window_ends = Vec::new();
+
+        let windows = windows.into_iter().zip(ranked).map(|((in_sample, out_of_sample), mut top)| {
+            let winner = top.pop();
+            let in_sample_score = winner.as_ref().map(|(profit, _)| profit.0);
+            if let Some((_, best)) = winner { setup = Some(best); }
+
+            let pnl = match &setup {
+                Some(setup) => {
+                    let (signal_in, signal_out) = setup.signal_lines(&self.lhc);
+                    let window_signals = self.trigger_signals(&signal_in, &signal_out, out_of_sample.clone());
+                    signals.extend(window_signals.iter().cloned());
+                    if !window_signals.is_empty() { window_ends.push(signals.len() - 1); }
+
+                    simulate(self.simulate_params(window_signals))
+                },
+                None => PnL::default(),
+            };
+
+            WalkForwardWindow { in_sample, out_of_sample, setup: setup.clone(), in_sample_score, pnl }
+        }).collect();
+
+        WalkForwardResult { windows, backtest: backtest_windows(self.simulate_params(signals), &window_ends) }
+    }
+}